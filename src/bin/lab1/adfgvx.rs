@@ -0,0 +1,176 @@
+use crate::{Cipher, Keyword};
+use std::fmt::Write;
+
+/// Алфавит квадрата Полибия
+#[derive(Debug, Clone, Copy)]
+pub enum Alphabet {
+    /// Латиница 5x5 без J (ADFGX)
+    Latin5,
+    /// Латиница и цифры 6x6 (ADFGVX)
+    Latin6,
+    /// Кириллица с пробелом, точкой и запятой 6x6 (ADFGVX)
+    Cyrillic6,
+}
+
+impl Alphabet {
+    fn symbols(&self) -> &'static str {
+        match self {
+            Alphabet::Latin5 => "ABCDEFGHIKLMNOPQRSTUVWXYZ",
+            Alphabet::Latin6 => "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789",
+            Alphabet::Cyrillic6 => "АБВГДЕЁЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯ .,",
+        }
+    }
+
+    /// Буквы, которыми обозначаются строки и столбцы квадрата
+    fn coordinates(&self) -> &'static [char] {
+        match self {
+            Alphabet::Latin5 => &['A', 'D', 'F', 'G', 'X'],
+            Alphabet::Latin6 | Alphabet::Cyrillic6 => &['A', 'D', 'F', 'G', 'V', 'X'],
+        }
+    }
+
+    /// Приведение символа к алфавиту квадрата: верхний регистр, J заменяется на I в квадрате 5x5
+    fn normalize(&self, c: char) -> char {
+        let c = c.to_uppercase().next().unwrap_or(c);
+        match (self, c) {
+            (Alphabet::Latin5, 'J') => 'I',
+            _ => c,
+        }
+    }
+}
+
+/// Квадрат Полибия, заполненный сначала неповторяющимися символами ключа, затем остатком алфавита
+#[derive(Debug)]
+pub struct Square {
+    alphabet: Alphabet,
+    cells: Vec<char>,
+}
+
+impl Square {
+    pub fn new(alphabet: Alphabet, keyword: &str) -> Self {
+        let mut cells = Vec::with_capacity(alphabet.symbols().len());
+        let keyword = keyword.chars().map(|c| alphabet.normalize(c));
+        for c in keyword.chain(alphabet.symbols().chars()) {
+            if alphabet.symbols().contains(c) && !cells.contains(&c) {
+                cells.push(c);
+            }
+        }
+        Self { alphabet, cells }
+    }
+
+    /// Координаты символа в квадрате, `None` для символов вне алфавита
+    fn encode(&self, c: char) -> Option<[char; 2]> {
+        let coordinates = self.alphabet.coordinates();
+        let index = self
+            .cells
+            .iter()
+            .position(|x| *x == self.alphabet.normalize(c))?;
+        Some([
+            coordinates[index / coordinates.len()],
+            coordinates[index % coordinates.len()],
+        ])
+    }
+
+    fn decode(&self, row: char, column: char) -> Option<char> {
+        let coordinates = self.alphabet.coordinates();
+        let row = coordinates.iter().position(|x| *x == row)?;
+        let column = coordinates.iter().position(|x| *x == column)?;
+        Some(self.cells[row * coordinates.len() + column])
+    }
+}
+
+/// Шифр ADFGVX: замена по квадрату Полибия с последующей столбцовой перестановкой `Cipher`.
+/// Символы вне алфавита квадрата при шифровании пропускаются
+#[derive(Debug)]
+pub struct Adfgvx {
+    square: Square,
+    stage: Cipher,
+}
+
+impl Adfgvx {
+    /// Конструктор пустого шифра из квадрата и ключа перестановки
+    pub fn new(square: Square, keyword: impl Into<Keyword>) -> Self {
+        Self {
+            square,
+            stage: Cipher::new(keyword),
+        }
+    }
+
+    /// Конструктор шифра из зашифрованных данных, квадрата и ключа перестановки
+    pub fn from_content(content: String, square: Square, keyword: impl Into<Keyword>) -> Self {
+        Self {
+            square,
+            stage: Cipher::from_content(content, keyword),
+        }
+    }
+
+    pub fn encrypt(self) -> String {
+        self.stage.encrypt()
+    }
+
+    /// Дешифрование, `None` если шифротекст содержит нечетное число символов или чужие координаты
+    pub fn decrypt(self) -> Option<String> {
+        let coordinates: Vec<char> = self.stage.decrypt().chars().collect();
+        if !coordinates.len().is_multiple_of(2) {
+            return None;
+        }
+        coordinates
+            .chunks(2)
+            .map(|pair| self.square.decode(pair[0], pair[1]))
+            .collect()
+    }
+}
+
+impl Write for Adfgvx {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for c in s.chars() {
+            if let Some(pair) = self.square.encode(c) {
+                self.stage.write_str(&String::from_iter(pair))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Adfgvx, Alphabet, Square};
+    use std::fmt::Write;
+
+    #[test]
+    fn adfgx_test() {
+        let square = || Square::new(Alphabet::Latin5, "BTALPDHOZKQFVSNGICUXMREWY");
+        let mut encrypt = Adfgvx::new(square(), String::from("CARGO"));
+        let _ = encrypt.write_str("attack at once");
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, "FAXDFADDDGDGFFFAFAXAFAFX");
+        let decrypt = Adfgvx::from_content(encrypted, square(), String::from("CARGO"));
+        assert_eq!(decrypt.decrypt(), Some(String::from("ATTACKATONCE")));
+    }
+
+    #[test]
+    fn adfgvx_test() {
+        let square = || Square::new(Alphabet::Latin6, "NA1C3H8TB2OME5WRPD4F6G7I9J0KLQSUVXYZ");
+        let mut encrypt = Adfgvx::new(square(), String::from("PRIVACY"));
+        let _ = encrypt.write_str("ATTACK AT 1200AM");
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, "DGDDDAGDDGAFADDFDADVDVFAADVX");
+        let decrypt = Adfgvx::from_content(encrypted, square(), String::from("PRIVACY"));
+        assert_eq!(decrypt.decrypt(), Some(String::from("ATTACKAT1200AM")));
+    }
+
+    #[test]
+    fn adfgvx_cyrillic_test() {
+        let initial_text = "Трус умирает каждый день, а воин ожидает свою гибель.";
+        let square = || Square::new(Alphabet::Cyrillic6, "шифровальщик");
+        let mut encrypt = Adfgvx::new(square(), String::from("ключик"));
+        let _ = encrypt.write_str(initial_text);
+        let encrypted = encrypt.encrypt();
+        assert!(encrypted.chars().all(|c| "ADFGVX".contains(c)));
+        let decrypt = Adfgvx::from_content(encrypted.clone(), square(), String::from("ключик"));
+        assert_eq!(decrypt.decrypt(), Some(initial_text.to_uppercase()));
+        let decrypt =
+            Adfgvx::from_content(encrypted[1..].to_string(), square(), String::from("ключик"));
+        assert_eq!(decrypt.decrypt(), None);
+    }
+}
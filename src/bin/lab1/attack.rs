@@ -0,0 +1,284 @@
+use crate::{Cipher, KeySymbol, Keyword};
+use log::debug;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+/// Образец русского текста для биграммной модели языка по умолчанию
+const RUSSIAN_SAMPLE: &str = "Информационная безопасность изучает способы защиты данных от \
+несанкционированного доступа, изменения и уничтожения. Одним из самых старых способов защиты \
+является шифрование, при котором исходное сообщение преобразуется так, чтобы его смысл был \
+понятен только тому, кто знает ключ. Шифры перестановки не меняют сами буквы, а лишь изменяют \
+их порядок, поэтому частоты отдельных букв в шифротексте остаются такими же, как в открытом \
+тексте. Это позволяет сразу отличить перестановку от замены и подсказывает, как ее вскрывать: \
+нужно найти такой порядок столбцов, при котором соседние буквы образуют привычные для языка \
+сочетания. Когда у противника есть несколько сообщений одинаковой длины, зашифрованных одним \
+и тем же ключом, задача становится заметно проще, потому что каждая перестановка столбцов \
+проверяется сразу на всех текстах. В старых армиях ключи меняли редко, и опытные криптографы \
+часто восстанавливали их по нескольким перехваченным телеграммам за один вечер. Сегодня такие \
+задачи решаются на компьютере за доли секунды, но сама идея метода осталась прежней. Хорошая \
+модель языка должна учитывать пробелы между словами, частые окончания и предлоги, а также то, \
+что после гласной обычно идет согласная, и наоборот. Мы будем оценивать каждый вариант \
+расшифровки суммой логарифмов вероятностей всех пар соседних символов и выбирать лучший.";
+
+/// Приведение символа к алфавиту модели: строчные буквы, остальные символы как пробел
+fn normalize(c: char) -> char {
+    if c.is_alphabetic() {
+        c.to_lowercase().next().unwrap_or(c)
+    } else {
+        ' '
+    }
+}
+
+/// Биграммная модель языка со сглаживанием Лапласа
+#[derive(Debug)]
+pub struct LanguageModel {
+    log_probs: HashMap<(char, char), f64>,
+    unknown: HashMap<char, f64>,
+    unseen: f64,
+}
+
+impl LanguageModel {
+    pub fn from_corpus(corpus: &str) -> Self {
+        let chars: Vec<char> = corpus.chars().map(normalize).collect();
+        let mut alphabet: Vec<char> = chars.clone();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        let size = alphabet.len().max(1) as f64;
+        let mut pairs: HashMap<(char, char), usize> = HashMap::new();
+        let mut firsts: HashMap<char, usize> = HashMap::new();
+        for pair in chars.windows(2) {
+            *pairs.entry((pair[0], pair[1])).or_default() += 1;
+            *firsts.entry(pair[0]).or_default() += 1;
+        }
+        let log_probs = pairs
+            .into_iter()
+            .map(|((a, b), count)| {
+                let total = firsts[&a] as f64 + size;
+                ((a, b), ((count as f64 + 1.0) / total).ln())
+            })
+            .collect();
+        let unknown = firsts
+            .into_iter()
+            .map(|(a, count)| (a, (1.0 / (count as f64 + size)).ln()))
+            .collect();
+        Self {
+            log_probs,
+            unknown,
+            unseen: (1.0 / size).ln(),
+        }
+    }
+
+    /// Сумма логарифмов вероятностей пар соседних символов текста
+    pub fn score(&self, text: &str) -> f64 {
+        let chars: Vec<char> = text.chars().map(normalize).collect();
+        chars
+            .windows(2)
+            .map(|pair| match self.log_probs.get(&(pair[0], pair[1])) {
+                Some(log_prob) => *log_prob,
+                None => *self.unknown.get(&pair[0]).unwrap_or(&self.unseen),
+            })
+            .sum()
+    }
+}
+
+impl Default for LanguageModel {
+    fn default() -> Self {
+        Self::from_corpus(RUSSIAN_SAMPLE)
+    }
+}
+
+/// Результат вскрытия: порядок чтения столбцов и открытые тексты
+#[derive(Debug)]
+pub struct Recovered {
+    /// Номер каждого столбца в порядке чтения, начиная с 1
+    pub ranks: Vec<usize>,
+    pub plaintexts: Vec<String>,
+    /// Средний логарифм вероятности биграммы открытых текстов
+    pub score: f64,
+}
+
+impl Recovered {
+    /// Числовой ключ, пригодный для дешифрования в lab1
+    pub fn keyword(&self) -> String {
+        ranks_keyword(&self.ranks)
+    }
+}
+
+/// Числовой ключ из номеров столбцов через запятую
+pub fn ranks_keyword(ranks: &[usize]) -> String {
+    let ranks: Vec<String> = ranks.iter().map(|r| r.to_string()).collect();
+    ranks.join(",")
+}
+
+/// Длина ключа, до которой перебираются все перестановки столбцов
+const EXHAUSTIVE_LIMIT: usize = 7;
+/// Число случайных стартов поиска восхождением для длинных ключей
+const RESTARTS: usize = 20;
+
+fn decrypt_all(ciphertexts: &[String], ranks: &[usize]) -> Vec<String> {
+    let keyword = Keyword(ranks.iter().map(|r| KeySymbol::Number(*r as u64)).collect());
+    ciphertexts
+        .iter()
+        .map(|c| Cipher::from_content(c.clone(), keyword.clone()).decrypt())
+        .collect()
+}
+
+/// Порядок чтения столбцов в номера столбцов
+pub fn ranks_of(order: &[usize]) -> Vec<usize> {
+    let mut ranks = vec![0; order.len()];
+    for (rank, column) in order.iter().enumerate() {
+        ranks[*column] = rank + 1;
+    }
+    ranks
+}
+
+/// Оценка порядка чтения столбцов по всем сообщениям сразу
+fn evaluate(ciphertexts: &[String], order: &[usize], model: &LanguageModel) -> Recovered {
+    let ranks = ranks_of(order);
+    let plaintexts = decrypt_all(ciphertexts, &ranks);
+    let pairs: usize = plaintexts
+        .iter()
+        .map(|p| p.chars().count().saturating_sub(1))
+        .sum();
+    let total: f64 = plaintexts.iter().map(|p| model.score(p)).sum();
+    Recovered {
+        ranks,
+        plaintexts,
+        score: total / pairs.max(1) as f64,
+    }
+}
+
+/// Следующая перестановка в лексикографическом порядке, `false` после последней
+fn next_permutation(order: &mut [usize]) -> bool {
+    let Some(i) = (1..order.len()).rev().find(|&i| order[i - 1] < order[i]) else {
+        return false;
+    };
+    let j = (i..order.len())
+        .rev()
+        .find(|&j| order[j] > order[i - 1])
+        .unwrap();
+    order.swap(i - 1, j);
+    order[i..].reverse();
+    true
+}
+
+/// Поиск восхождением: перестановки двух столбцов и перенос столбца на другое место
+fn hill_climb(ciphertexts: &[String], mut order: Vec<usize>, model: &LanguageModel) -> Recovered {
+    let mut best = evaluate(ciphertexts, &order, model);
+    loop {
+        let mut improved = false;
+        for i in 0..order.len() {
+            for j in 0..order.len() {
+                if i == j {
+                    continue;
+                }
+                let mut swapped = order.clone();
+                swapped.swap(i, j);
+                let mut moved = order.clone();
+                let column = moved.remove(i);
+                moved.insert(j, column);
+                for candidate in [swapped, moved] {
+                    let recovered = evaluate(ciphertexts, &candidate, model);
+                    if recovered.score > best.score {
+                        best = recovered;
+                        order = candidate;
+                        improved = true;
+                    }
+                }
+            }
+        }
+        if !improved {
+            return best;
+        }
+    }
+}
+
+/// Лучший порядок столбцов для ключа заданной длины
+fn search_key_length(ciphertexts: &[String], length: usize, model: &LanguageModel) -> Recovered {
+    let mut order: Vec<usize> = (0..length).collect();
+    let mut best = evaluate(ciphertexts, &order, model);
+    if length <= EXHAUSTIVE_LIMIT {
+        while next_permutation(&mut order) {
+            let recovered = evaluate(ciphertexts, &order, model);
+            if recovered.score > best.score {
+                best = recovered;
+            }
+        }
+        return best;
+    }
+    for _ in 0..RESTARTS {
+        order.shuffle(&mut thread_rng());
+        let recovered = hill_climb(ciphertexts, order.clone(), model);
+        if recovered.score > best.score {
+            best = recovered;
+        }
+    }
+    best
+}
+
+/// Множественная анаграммация: поиск общего порядка столбцов для нескольких сообщений,
+/// зашифрованных одним ключом, максимизирующего суммарную оценку открытых текстов моделью языка
+pub fn multiple_anagram(
+    ciphertexts: &[String],
+    key_lengths: RangeInclusive<usize>,
+    model: &LanguageModel,
+) -> Option<Recovered> {
+    key_lengths
+        .filter(|length| *length > 0)
+        .map(|length| {
+            let recovered = search_key_length(ciphertexts, length, model);
+            debug!(
+                "Key length {length}: keyword {}, score {:.4}",
+                recovered.keyword(),
+                recovered.score
+            );
+            recovered
+        })
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{multiple_anagram, next_permutation, LanguageModel};
+    use crate::Cipher;
+    use std::fmt::Write;
+
+    #[test]
+    fn permutations_test() {
+        let mut order = vec![0, 1, 2];
+        let mut count = 1;
+        while next_permutation(&mut order) {
+            count += 1;
+        }
+        assert_eq!(count, 6);
+        assert_eq!(order, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn model_prefers_language() {
+        let model = LanguageModel::default();
+        assert!(model.score("перестановка букв") > model.score("рпеетсаонвак укб"));
+    }
+
+    #[test]
+    fn multiple_anagram_test() {
+        let messages = [
+            "завтра утром отправляйте все донесения в штаб северного фронта",
+            "противник сосредоточил резервы возле старой переправы через реку",
+            "связь с третьим полком потеряна после полуночи, ждем указаний",
+        ];
+        let ciphertexts: Vec<String> = messages
+            .iter()
+            .map(|m| {
+                let mut encrypt = Cipher::new(String::from("ключ"));
+                let _ = encrypt.write_str(m);
+                encrypt.encrypt()
+            })
+            .collect();
+        let recovered = multiple_anagram(&ciphertexts, 2..=6, &LanguageModel::default()).unwrap();
+        assert_eq!(recovered.keyword(), "1,2,4,3");
+        assert_eq!(recovered.plaintexts, messages);
+    }
+}
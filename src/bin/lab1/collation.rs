@@ -0,0 +1,65 @@
+use clap::ValueEnum;
+
+/// Русский алфавит в учебном порядке, ё следует за е
+const RUSSIAN_ALPHABET: &str = "абвгдеёжзийклмнопрстуфхцчшщъыьэюя";
+
+/// Порядок сортировки букв ключевого слова, определяющий порядок чтения столбцов
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// По кодам символов Unicode: ё после я, заглавные раньше строчных
+    #[default]
+    Codepoint,
+    /// По русскому алфавиту с ё после е без учета регистра, остальные символы по кодам
+    Russian,
+    /// По кодам символов Unicode без учета регистра
+    CaseInsensitive,
+}
+
+fn to_lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+impl Collation {
+    /// Вес буквы ключа, буквы с равным весом упорядочиваются по позиции в ключе
+    pub fn weight(&self, c: char) -> u64 {
+        match self {
+            Collation::Codepoint => c as u64,
+            Collation::CaseInsensitive => to_lowercase(c) as u64,
+            Collation::Russian => {
+                // Буквы русского алфавита занимают промежуток между весами 'а' и следующего за ней символа
+                let base = (c as u64) << 8;
+                match RUSSIAN_ALPHABET.chars().position(|x| x == to_lowercase(c)) {
+                    Some(index) => (('а' as u64) << 8) + index as u64,
+                    None => base,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Collation;
+
+    fn sorted(keyword: &str, collation: Collation) -> String {
+        let mut chars: Vec<char> = keyword.chars().collect();
+        chars.sort_by_key(|c| collation.weight(*c));
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn codepoint_test() {
+        assert_eq!(sorted("яёеЯ", Collation::Codepoint), "Яеяё");
+    }
+
+    #[test]
+    fn russian_test() {
+        assert_eq!(sorted("яёеЖа", Collation::Russian), "аеёЖя");
+        assert_eq!(sorted("ёbяa", Collation::Russian), "abёя");
+    }
+
+    #[test]
+    fn case_insensitive_test() {
+        assert_eq!(sorted("bBaA", Collation::CaseInsensitive), "aAbB");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::route::Route;
+use crate::{column_order, Keyword};
+
+/// Перестановка с нарушенным заполнением: таблица сначала заполняется треугольниками,
+/// каждый из которых начинается со столбца, следующего по порядку ключа, а затем
+/// оставшиеся ячейки заполняются по строкам. Столбцы читаются в порядке ключа, как в `Cipher`
+#[derive(Debug)]
+pub struct Disrupted {
+    order: Vec<usize>,
+}
+
+impl Disrupted {
+    pub fn new(keyword: impl Into<Keyword>) -> Self {
+        let keyword = keyword.into().0;
+        assert!(!keyword.is_empty(), "Keyword must not be empty");
+        Self {
+            order: column_order(&keyword),
+        }
+    }
+
+    /// Число ячеек каждой строки, входящих в треугольники
+    fn triangle_widths(&self, rows: usize) -> Vec<usize> {
+        let columns = self.order.len();
+        let mut widths = Vec::with_capacity(rows);
+        let mut rank = 0;
+        while widths.len() < rows {
+            let mut width = self.order[rank % columns] + 1;
+            while widths.len() < rows && width <= columns {
+                widths.push(width);
+                width += 1;
+            }
+            rank += 1;
+        }
+        widths
+    }
+}
+
+impl Route for Disrupted {
+    fn read_order(&self, len: usize) -> Vec<usize> {
+        let columns = self.order.len();
+        let rows = len.div_ceil(columns);
+        let widths = self.triangle_widths(rows);
+        // Ячейки таблицы в порядке записи открытого текста: сначала треугольники, затем остаток
+        let triangles = (0..len).filter(|cell| cell % columns < widths[cell / columns]);
+        let rest = (0..len).filter(|cell| cell % columns >= widths[cell / columns]);
+        let mut plain_position = vec![0; len];
+        for (i, cell) in triangles.chain(rest).enumerate() {
+            plain_position[cell] = i;
+        }
+        self.order
+            .iter()
+            .flat_map(|column| (*column..len).step_by(columns))
+            .map(|cell| plain_position[cell])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Disrupted;
+    use crate::route::RouteCipher;
+    use std::fmt::Write;
+
+    #[test]
+    fn disrupted_test() {
+        // A K L M
+        // B C N O
+        // D E F P
+        // G H I J
+        let mut encrypt = RouteCipher::new(Disrupted::new(String::from("ключ")));
+        let _ = encrypt.write_str("ABCDEFGHIJKLMNOP");
+        assert_eq!(encrypt.encrypt(), "ABDGKCEHMOPJLNFI");
+    }
+
+    #[test]
+    fn disrupted_complex_test() {
+        let text: Vec<char> = "Трус умирает каждый день, а воин ожидает свою гибель"
+            .chars()
+            .collect();
+        for keyword in ["шифр", "ключик", "перестановка", "3,1,2"] {
+            for len in 0..=text.len() {
+                let initial_text: String = text[..len].iter().collect();
+                let mut encrypt = RouteCipher::new(Disrupted::new(keyword.to_string()));
+                let _ = encrypt.write_str(&initial_text);
+                let decrypt = RouteCipher::from_content(
+                    encrypt.encrypt(),
+                    Disrupted::new(keyword.to_string()),
+                );
+                assert_eq!(decrypt.decrypt(), initial_text);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{Cipher, Keyword};
+use clap::ValueEnum;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Форма нормализации Unicode открытого текста
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum Normalization {
+    /// Каноническая композиция: й - один символ
+    Nfc,
+    /// Каноническая декомпозиция: й - и с комбинируемым знаком
+    Nfd,
+}
+
+pub fn normalize(text: &str, normalization: Normalization) -> String {
+    match normalization {
+        Normalization::Nfc => text.nfc().collect(),
+        Normalization::Nfd => text.nfd().collect(),
+    }
+}
+
+/// Расширенные графемные кластеры текста
+fn graphemes(text: &str) -> Vec<String> {
+    text.graphemes(true).map(String::from).collect()
+}
+
+/// Шифрование с перестановкой графемных кластеров вместо отдельных символов `char`.
+/// `None`, если кластеры в шифротексте сливаются с соседними (например, одиночный
+/// комбинируемый знак в начале текста) и дешифрование не смогло бы восстановить их границы
+pub fn encrypt_graphemes(text: &str, keyword: impl Into<Keyword>) -> Option<String> {
+    let mut encrypt = Cipher::with_keyword(keyword);
+    graphemes(text)
+        .into_iter()
+        .for_each(|g| encrypt.add_symbol(g));
+    let encrypted = encrypt.encrypt_symbols();
+    let ans = encrypted.concat();
+    if graphemes(&ans) != encrypted {
+        return None;
+    }
+    Some(ans)
+}
+
+/// Дешифрование текста, зашифрованного перестановкой графемных кластеров
+pub fn decrypt_graphemes(content: &str, keyword: impl Into<Keyword>) -> String {
+    Cipher::from_symbols(graphemes(content), keyword)
+        .decrypt_symbols()
+        .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_graphemes, encrypt_graphemes, normalize, Normalization};
+    use crate::Cipher;
+    use std::fmt::Write;
+
+    #[test]
+    fn grapheme_round_trip() {
+        let initial_text = normalize("йогурт и чай, café, 👨‍👩‍👧 🇷🇺", Normalization::Nfd);
+        assert!(initial_text.contains('\u{0306}'));
+        for keyword in ["шифр", "ключик", "2,1"] {
+            let encrypted = encrypt_graphemes(&initial_text, keyword.to_string()).unwrap();
+            assert!(!encrypted.starts_with('\u{0306}'));
+            assert_eq!(encrypted.chars().count(), initial_text.chars().count());
+            assert_eq!(
+                decrypt_graphemes(&encrypted, keyword.to_string()),
+                initial_text
+            );
+        }
+    }
+
+    #[test]
+    fn grapheme_keeps_combining_marks() {
+        // и + U+0306 при посимвольной перестановке отрывается от буквы
+        let initial_text = "и\u{0306}и\u{0306}";
+        let mut encrypt = Cipher::new(String::from("ба"));
+        let _ = encrypt.write_str(initial_text);
+        assert_eq!(encrypt.encrypt(), "\u{0306}\u{0306}ии");
+        let encrypted = encrypt_graphemes(initial_text, String::from("ба")).unwrap();
+        assert_eq!(encrypted, initial_text);
+    }
+
+    #[test]
+    fn grapheme_fusing_clusters() {
+        // Одиночный комбинируемый знак в начале текста сольется с буквой после перестановки
+        assert_eq!(encrypt_graphemes("\u{0306}и", String::from("ба")), None);
+    }
+
+    #[test]
+    fn normalization_test() {
+        assert_eq!(normalize("й", Normalization::Nfd), "и\u{0306}");
+        assert_eq!(normalize("и\u{0306}", Normalization::Nfc), "й");
+    }
+}
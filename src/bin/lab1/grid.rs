@@ -0,0 +1,113 @@
+use crate::{column_lengths, column_order, Cipher};
+use console::{measure_text_width, pad_str, Alignment};
+
+/// Пробел в таблице отображается видимым символом, чтобы отличать его от пустой ячейки
+fn show_char(c: &char) -> String {
+    match c {
+        ' ' => String::from("␣"),
+        c => c.to_string(),
+    }
+}
+
+/// Таблица шифра: строка ключа, строка номеров столбцов в порядке чтения и заполненные строки.
+/// Недостающие ячейки последней неполной строки остаются пустыми
+pub fn render_grid(cipher: &Cipher) -> String {
+    let columns = cipher.keyword.len();
+    let mut rank = vec![0; columns];
+    for (i, column) in column_order(&cipher.keyword).into_iter().enumerate() {
+        rank[column] = i + 1;
+    }
+    let mut by_column: Vec<&[char]> = vec![&[]; columns];
+    for ((_, column), cell) in cipher.words.iter() {
+        by_column[*column] = cell;
+    }
+    let rows = by_column.iter().map(|v| v.len()).max().unwrap_or(0);
+
+    let mut table: Vec<Vec<String>> = vec![
+        cipher.keyword.iter().map(|s| s.to_string()).collect(),
+        rank.iter().map(|r| r.to_string()).collect(),
+    ];
+    for row in 0..rows {
+        table.push(
+            by_column
+                .iter()
+                .map(|column| column.get(row).map(show_char).unwrap_or_default())
+                .collect(),
+        );
+    }
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            table
+                .iter()
+                .map(|row| measure_text_width(&row[column]))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut ans = String::new();
+    for (i, row) in table.iter().enumerate() {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!(" {} ", pad_str(cell, *width, Alignment::Center, None)))
+            .collect();
+        ans.push_str(line.join("|").trim_end());
+        ans.push('\n');
+        if i == 1 {
+            let line: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+            ans.push_str(&line.join("+"));
+            ans.push('\n');
+        }
+    }
+    ans
+}
+
+/// Пошаговое описание того, как `Cipher::from_content` распределяет шифротекст по столбцам
+pub fn render_trace(cipher: &Cipher) -> String {
+    let len = cipher.position;
+    let columns = cipher.keyword.len();
+    let mut ans = format!("Column lengths for {len} characters and {columns} columns:\n");
+    for (symbol, step) in cipher.keyword.iter().zip(column_lengths(len, columns)) {
+        ans.push_str(&format!(
+            "{symbol}: ceil({} / {}) = {}\n",
+            step.remaining, step.columns_left, step.length
+        ));
+    }
+    ans.push_str("Ciphertext split in column order:\n");
+    for ((symbol, column), cell) in cipher.words.iter() {
+        let cell: String = cell.iter().collect();
+        ans.push_str(&format!("{symbol} (column {}): \"{cell}\"\n", column + 1));
+    }
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_grid, render_trace};
+    use crate::Cipher;
+    use std::fmt::Write;
+
+    #[test]
+    fn grid_test() {
+        let mut encrypt = Cipher::new(String::from("шифр"));
+        let _ = encrypt.write_str("перестановочный");
+        let grid = render_grid(&encrypt);
+        assert_eq!(
+            grid,
+            " ш | и | ф | р\n 4 | 1 | 3 | 2\n---+---+---+---\n п | е | р | е\n с | т | а | н\n о | в | о | ч\n н | ы | й |\n"
+        );
+        let decrypt = Cipher::from_content(encrypt.encrypt(), String::from("шифр"));
+        assert_eq!(render_grid(&decrypt), grid);
+    }
+
+    #[test]
+    fn trace_test() {
+        let decrypt =
+            Cipher::from_content(String::from("етвыиенч рраойфпсонш"), String::from("шифр"));
+        let trace = render_trace(&decrypt);
+        assert!(trace
+            .starts_with("Column lengths for 20 characters and 4 columns:\nш: ceil(20 / 4) = 5\n"));
+        assert!(trace.contains("и (column 2): \"етвыи\"\n"));
+    }
+}
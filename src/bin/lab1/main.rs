@@ -0,0 +1,1230 @@
+mod adfgvx;
+mod attack;
+mod collation;
+mod disrupted;
+mod grapheme;
+mod grid;
+mod recover;
+mod route;
+mod stream;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_verbosity_flag::{Verbosity, WarnLevel};
+use log::{debug, error, trace, warn};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Write};
+use std::fs;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use adfgvx::{Adfgvx, Alphabet, Square};
+use attack::{multiple_anagram, LanguageModel};
+use collation::Collation;
+use disrupted::Disrupted;
+use grapheme::{decrypt_graphemes, encrypt_graphemes, normalize, Normalization};
+use grid::{render_grid, render_trace};
+use recover::{recover_key, KnownPair};
+use route::{Diagonal, Direction, RailFence, Route, RouteCipher, Snake, Spiral};
+use stream::{decrypt_stream, encrypt_stream, is_block_ciphertext};
+
+/// Проверка строки на удовлетворение условиям ключа - непустая и без управляющих символов
+fn check_keyword<T>(s: &T) -> bool
+where
+    T: Borrow<str>,
+{
+    let str = s.borrow();
+    !str.is_empty() && !str.chars().any(char::is_control)
+}
+
+/// Символ ключа: буква ключевого слова с весом в выбранном порядке сортировки или номер столбца числового ключа.
+/// Символы сравниваются только по весу
+#[derive(Debug, Clone)]
+enum KeySymbol {
+    Number(u64),
+    Letter { letter: char, weight: u64 },
+}
+
+impl KeySymbol {
+    fn weight(&self) -> u64 {
+        match self {
+            KeySymbol::Number(n) => *n,
+            KeySymbol::Letter { weight, .. } => *weight,
+        }
+    }
+}
+
+impl PartialEq for KeySymbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.weight() == other.weight()
+    }
+}
+
+impl Eq for KeySymbol {}
+
+impl PartialOrd for KeySymbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeySymbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.weight().cmp(&other.weight())
+    }
+}
+
+impl Display for KeySymbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySymbol::Number(n) => write!(f, "{n}"),
+            KeySymbol::Letter { letter, .. } => write!(f, "{letter}"),
+        }
+    }
+}
+
+/// Разбор ключа. Числа, разделенные запятыми, дефисами или пробелами, задают порядок столбцов напрямую,
+/// иначе порядок задают буквы ключевого слова согласно `collation`. Повторяющиеся символы упорядочиваются слева направо
+fn parse_keyword(keyword: &str, collation: Collation) -> Vec<KeySymbol> {
+    let is_separator = |c: char| c == ',' || c == '-' || c.is_whitespace();
+    if keyword.contains(is_separator) {
+        let numbers: Option<Vec<u64>> = keyword
+            .split(is_separator)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().ok())
+            .collect();
+        if let Some(numbers) = numbers.filter(|n| !n.is_empty()) {
+            return numbers.into_iter().map(KeySymbol::Number).collect();
+        }
+    }
+    keyword
+        .chars()
+        .map(|letter| KeySymbol::Letter {
+            letter,
+            weight: collation.weight(letter),
+        })
+        .collect()
+}
+
+/// Разобранный ключ. Из строки получается с порядком сортировки по кодам символов
+#[derive(Debug, Clone)]
+struct Keyword(Vec<KeySymbol>);
+
+impl Keyword {
+    fn new(keyword: &str, collation: Collation) -> Self {
+        Self(parse_keyword(keyword, collation))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl From<String> for Keyword {
+    fn from(keyword: String) -> Self {
+        Self::new(&keyword, Collation::default())
+    }
+}
+
+impl From<&str> for Keyword {
+    fn from(keyword: &str) -> Self {
+        Self::new(keyword, Collation::default())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(flatten)]
+    verbosity: Verbosity<WarnLevel>,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    args: Option<Args>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Множественная анаграммация: вскрытие столбцовой перестановки по нескольким
+    /// шифротекстам, зашифрованным одним ключом
+    Attack(AttackArgs),
+    /// Восстановление ключа столбцовой перестановки по известным парам шифротекст - открытый текст
+    RecoverKey(RecoverKeyArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct RecoverKeyArgs {
+    /// Файлы шифротекста и открытого текста, открытый текст может быть фрагментом с неизвестным смещением
+    #[arg(long, num_args = 2, value_names = ["CIPHERTEXT", "PLAINTEXT"], required = true)]
+    pair: Vec<PathBuf>,
+    /// Наименьшая проверяемая длина ключа
+    #[arg(long, default_value_t = 1)]
+    min_key: usize,
+    /// Наибольшая проверяемая длина ключа
+    #[arg(long, default_value_t = 20)]
+    max_key: usize,
+}
+
+#[derive(clap::Args, Debug)]
+struct AttackArgs {
+    /// Файлы шифротекстов, зашифрованных одним ключом
+    #[arg(required = true)]
+    ciphertexts: Vec<PathBuf>,
+    /// Наименьшая проверяемая длина ключа
+    #[arg(long, default_value_t = 2)]
+    min_key: usize,
+    /// Наибольшая проверяемая длина ключа
+    #[arg(long, default_value_t = 10)]
+    max_key: usize,
+    /// Текст для построения биграммной модели языка, по умолчанию встроенный русский образец
+    #[arg(long)]
+    corpus: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct Args {
+    running_mode: RunningMode,
+    input_file: PathBuf,
+    keyword: String,
+    output_file: Option<PathBuf>,
+    /// Вариант перестановки
+    #[arg(long, value_enum, default_value_t = CipherKind::Columnar)]
+    cipher: CipherKind,
+    /// Ключ второго прохода двойной перестановки
+    #[arg(long)]
+    second_keyword: Option<String>,
+    /// Заполнение таблицы до полного прямоугольника пустым символом, без значения выбирается случайный
+    #[arg(long)]
+    fill: Option<Option<char>>,
+    /// Число строк шифра ограждения или ширина таблицы маршрутных перестановок, по умолчанию длина ключа
+    #[arg(long)]
+    width: Option<usize>,
+    /// Сдвиг начала зигзага шифра ограждения
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+    /// Ключ квадрата Полибия шифров ADFGX/ADFGVX
+    #[arg(long, default_value = "")]
+    square_keyword: String,
+    /// Вывод таблицы столбцовой перестановки
+    #[arg(long)]
+    show_grid: bool,
+    /// Вывод распределения шифротекста по столбцам при дешифровании
+    #[arg(long)]
+    trace: bool,
+    /// Потоковое шифрование блоками из заданного числа строк таблицы, без чтения всего файла в память.
+    /// При дешифровании размер блока берется из заголовка шифротекста и должен совпадать с заданным
+    #[arg(long)]
+    block_rows: Option<usize>,
+    /// Потоковое дешифрование с размером блока из заголовка шифротекста
+    #[arg(long)]
+    stream: bool,
+    /// Перестановка байтов вместо символов, для двоичных файлов. Файл обрабатывается без изменений,
+    /// включая завершающий перевод строки
+    #[arg(long)]
+    bytes: bool,
+    /// Перестановка расширенных графемных кластеров вместо отдельных символов
+    #[arg(long)]
+    graphemes: bool,
+    /// Нормализация Unicode открытого текста перед шифрованием
+    #[arg(long, value_enum)]
+    normalization: Option<Normalization>,
+    /// Порядок сортировки букв ключа
+    #[arg(long, value_enum, default_value_t = Collation::Codepoint)]
+    collation: Collation,
+}
+
+#[derive(ValueEnum, Debug, Clone)]
+enum CipherKind {
+    /// Столбцовая перестановка, повторы букв ключа упорядочиваются слева направо
+    Columnar,
+    /// Перестановка Мышковского, столбцы с одинаковой буквой ключа читаются вместе по строкам
+    Myszkowski,
+    /// Двойная столбцовая перестановка с двумя независимыми ключами
+    Double,
+    /// Шифр ограждения (зигзаг по строкам)
+    RailFence,
+    /// Чтение таблицы по спирали по часовой стрелке
+    SpiralClockwise,
+    /// Чтение таблицы по спирали против часовой стрелки
+    SpiralCounterClockwise,
+    /// Чтение строк таблицы попеременно в разных направлениях
+    Snake,
+    /// Чтение таблицы по диагоналям
+    Diagonal,
+    /// Столбцовая перестановка с заполнением таблицы треугольниками, заданными ключом
+    Disrupted,
+    /// Квадрат Полибия 5x5 на латинице с последующей столбцовой перестановкой
+    Adfgx,
+    /// Квадрат Полибия 6x6 на латинице и цифрах с последующей столбцовой перестановкой
+    Adfgvx,
+    /// Квадрат Полибия 6x6 на кириллице с последующей столбцовой перестановкой
+    AdfgvxCyrillic,
+}
+
+#[derive(ValueEnum, Debug, Clone)]
+enum RunningMode {
+    Encrypt,
+    Decrypt,
+}
+
+/// Ключ столбца: символ ключа и его позиция, позиция разрешает повторы символов слева направо
+type ColumnKey = (KeySymbol, usize);
+
+/// Номера столбцов в порядке их чтения, заданном ключом
+fn column_order(keyword: &[KeySymbol]) -> Vec<usize> {
+    let columns: BTreeMap<ColumnKey, usize> = keyword
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| ((symbol.to_owned(), i), i))
+        .collect();
+    columns.into_values().collect()
+}
+
+/// Шаг распределения шифротекста по столбцам при дешифровании: столбцу достается
+/// оставшееся число символов, деленное на число оставшихся столбцов с округлением вверх
+#[derive(Debug)]
+struct ColumnLength {
+    remaining: usize,
+    columns_left: usize,
+    length: usize,
+}
+
+/// Длины столбцов неполной таблицы в порядке следования символов ключа
+fn column_lengths(len: usize, columns: usize) -> Vec<ColumnLength> {
+    let mut in_use = 0;
+    let mut steps = Vec::with_capacity(columns);
+    for i in 0..columns {
+        let remaining = len - in_use;
+        let columns_left = columns - i;
+        let length = remaining.div_ceil(columns_left);
+        steps.push(ColumnLength {
+            remaining,
+            columns_left,
+            length,
+        });
+        in_use += length;
+    }
+    steps
+}
+
+/// Объект шифра, может использоваться как для шифрования текста с помощью ключа так и для дешифрования уже имеющегося.
+/// Переставляет символы `char` или произвольные другие единицы, например байты
+#[derive(Debug)]
+struct Cipher<T = char> {
+    keyword: Vec<KeySymbol>,
+    position: usize,
+    words: BTreeMap<ColumnKey, Vec<T>>,
+}
+
+impl<T: Clone> Cipher<T> {
+    /// Конструктор пустого шифра из ключа для произвольных единиц перестановки
+    fn with_keyword(keyword: impl Into<Keyword>) -> Self {
+        let keyword = keyword.into().0;
+        let mut words = BTreeMap::new();
+        for (i, symbol) in keyword.iter().enumerate() {
+            words.insert((symbol.to_owned(), i), Vec::new());
+        }
+        Self {
+            keyword,
+            position: 0,
+            words,
+        }
+    }
+
+    /// Конструктор шифра из зашифрованной последовательности и ключа с помощью которого проводилось шифрование
+    fn from_symbols(content: Vec<T>, keyword: impl Into<Keyword>) -> Self {
+        let mut cipher = Self::with_keyword(keyword);
+        let keyword = &cipher.keyword;
+        let mut column_to_use = Vec::with_capacity(keyword.len());
+        for (c, step) in keyword
+            .iter()
+            .zip(column_lengths(content.len(), keyword.len()))
+        {
+            let to_use = step.length;
+            trace!("Column {c}: {to_use} symbols");
+            column_to_use.push(to_use);
+        }
+        let mut in_use = 0;
+        for ((_, i), v) in cipher.words.iter_mut() {
+            let to_use = column_to_use[*i];
+            v.extend_from_slice(&content[in_use..in_use + to_use]);
+            in_use += to_use;
+        }
+        cipher.position = content.len();
+        cipher
+    }
+
+    /// Ключ столбца, в который попадает символ на заданной позиции текста
+    fn column_key(&self, position: usize) -> ColumnKey {
+        let column = position % self.keyword.len();
+        (self.keyword[column].to_owned(), column)
+    }
+
+    fn add_symbol(&mut self, c: T) {
+        let current_key = self.column_key(self.position);
+        self.words
+            .get_mut(&current_key)
+            .expect("Must init map with char in constructor")
+            .push(c);
+        self.position += 1;
+    }
+
+    /// Дополнение последней строки таблицы пустым символом до полной длины ключа
+    fn fill(&mut self, null: T) {
+        while !self.position.is_multiple_of(self.keyword.len()) {
+            self.add_symbol(null.clone());
+        }
+    }
+
+    fn decrypt_symbols(self) -> Vec<T> {
+        CipherIterator::new(self).collect()
+    }
+
+    fn encrypt_symbols(self) -> Vec<T> {
+        let mut ans = Vec::new();
+        for (_, v) in self.words.into_iter() {
+            ans.extend_from_slice(v.as_slice());
+        }
+        ans
+    }
+}
+
+impl Cipher {
+    /// Конструктор пустого шифра из ключа
+    fn new(keyword: impl Into<Keyword>) -> Self {
+        Self::with_keyword(keyword)
+    }
+
+    /// Конструктор шифра из зашифрованных данных и ключа с помощью которого проводилось шифрование
+    fn from_content(content: String, keyword: impl Into<Keyword>) -> Self {
+        Self::from_symbols(content.chars().collect(), keyword)
+    }
+
+    fn decrypt(self) -> String {
+        self.decrypt_symbols().iter().collect()
+    }
+
+    /// Конструктор шифра из данных, зашифрованных с заполнением таблицы до полного прямоугольника.
+    /// Длина шифротекста должна быть кратна длине ключа
+    fn from_filled_content(content: String, keyword: impl Into<Keyword>) -> Option<Self> {
+        let keyword = keyword.into();
+        let columns = keyword.len();
+        if columns == 0 || !content.chars().count().is_multiple_of(columns) {
+            return None;
+        }
+        Some(Self::from_content(content, keyword))
+    }
+
+    /// Дешифрование с удалением пустых символов, дополнявших последнюю строку
+    fn decrypt_filled(self, null: char) -> String {
+        let max_fill = self.keyword.len() - 1;
+        let mut ans = self.decrypt_symbols();
+        let mut stripped = 0;
+        while stripped < max_fill && ans.last() == Some(&null) {
+            ans.pop();
+            stripped += 1;
+        }
+        ans.iter().collect()
+    }
+
+    fn encrypt(self) -> String {
+        self.encrypt_symbols().iter().collect()
+    }
+}
+
+impl Write for Cipher {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for c in s.chars() {
+            self.add_symbol(c);
+        }
+        Ok(())
+    }
+}
+
+/// Структура итератора над шифром
+struct CipherIterator<T> {
+    cipher: Cipher<T>,
+    position: usize,
+}
+
+impl<T> CipherIterator<T> {
+    pub fn new(cipher: Cipher<T>) -> Self {
+        Self {
+            cipher,
+            position: 0,
+        }
+    }
+}
+
+impl<T: Clone> Iterator for CipherIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_key = self.cipher.column_key(self.position);
+        let ans = self.cipher.words[&current_key]
+            .get(self.position / self.cipher.keyword.len())
+            .map(|x| x.to_owned());
+        self.position += 1;
+        ans
+    }
+}
+
+/// Перестановка Мышковского: столбцы с одинаковым символом ключа читаются вместе построчно
+#[derive(Debug)]
+struct Myszkowski {
+    keyword: Vec<KeySymbol>,
+    content: Vec<char>,
+}
+
+impl Myszkowski {
+    /// Конструктор пустого шифра из ключа
+    fn new(keyword: impl Into<Keyword>) -> Self {
+        Self {
+            keyword: keyword.into().0,
+            content: Vec::new(),
+        }
+    }
+
+    /// Конструктор шифра из зашифрованных данных и ключа с помощью которого проводилось шифрование
+    fn from_content(content: String, keyword: impl Into<Keyword>) -> Self {
+        Self {
+            keyword: keyword.into().0,
+            content: content.chars().collect(),
+        }
+    }
+
+    /// Позиции символов открытого текста в порядке их следования в шифротексте
+    fn read_order(&self) -> Vec<usize> {
+        let mut groups: BTreeMap<&KeySymbol, Vec<usize>> = BTreeMap::new();
+        for (i, symbol) in self.keyword.iter().enumerate() {
+            groups.entry(symbol).or_default().push(i);
+        }
+        let len = self.content.len();
+        let columns = self.keyword.len();
+        let rows = len.div_ceil(columns);
+        let mut order = Vec::with_capacity(len);
+        for group in groups.values() {
+            for row in 0..rows {
+                for column in group {
+                    let position = row * columns + column;
+                    if position < len {
+                        order.push(position);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    fn encrypt(self) -> String {
+        self.read_order()
+            .into_iter()
+            .map(|i| self.content[i])
+            .collect()
+    }
+
+    fn decrypt(self) -> String {
+        let mut ans = self.content.clone();
+        for (c, i) in self.content.iter().zip(self.read_order()) {
+            ans[i] = *c;
+        }
+        ans.iter().collect()
+    }
+}
+
+impl Write for Myszkowski {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.content.extend(s.chars());
+        Ok(())
+    }
+}
+
+/// Двойная перестановка: два последовательных прохода `Cipher` с независимыми ключами
+#[derive(Debug)]
+struct DoubleCipher {
+    stage: Cipher,
+    keyword: Keyword,
+}
+
+impl DoubleCipher {
+    /// Конструктор пустого шифра из ключей первого и второго прохода
+    fn new(first_keyword: impl Into<Keyword>, second_keyword: impl Into<Keyword>) -> Self {
+        Self {
+            stage: Cipher::new(first_keyword),
+            keyword: second_keyword.into(),
+        }
+    }
+
+    /// Конструктор шифра из зашифрованных данных и ключей, с помощью которых проводилось шифрование
+    fn from_content(
+        content: String,
+        first_keyword: impl Into<Keyword>,
+        second_keyword: impl Into<Keyword>,
+    ) -> Self {
+        Self {
+            stage: Cipher::from_content(content, second_keyword),
+            keyword: first_keyword.into(),
+        }
+    }
+
+    fn encrypt(self) -> String {
+        let mut second = Cipher::new(self.keyword);
+        let _ = second.write_str(&self.stage.encrypt());
+        second.encrypt()
+    }
+
+    fn decrypt(self) -> String {
+        Cipher::from_content(self.stage.decrypt(), self.keyword).decrypt()
+    }
+}
+
+impl Write for DoubleCipher {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.stage.write_str(s)
+    }
+}
+
+/// Случайный пустой символ, не встречающийся в тексте
+fn random_null(content: &str) -> Option<char> {
+    let candidates: Vec<char> = ('а'..='я')
+        .chain('a'..='z')
+        .filter(|c| !content.contains(*c))
+        .collect();
+    candidates.choose(&mut thread_rng()).copied()
+}
+
+/// Шифрование или дешифрование маршрутной перестановкой
+fn transpose<R: Route>(route: R, mode: RunningMode, content: String) -> String {
+    match mode {
+        RunningMode::Encrypt => {
+            let mut encrypt = RouteCipher::new(route);
+            let _ = encrypt.write_str(&content);
+            encrypt.encrypt()
+        }
+        RunningMode::Decrypt => RouteCipher::from_content(content, route).decrypt(),
+    }
+}
+
+/// Вывод таблицы и распределения по столбцам столбцовой перестановки по запросу
+fn show_cipher(cipher: &Cipher, show_grid: bool, trace: bool) {
+    if trace {
+        eprint!("{}", render_trace(cipher));
+    }
+    if show_grid {
+        eprint!("{}", render_grid(cipher));
+    }
+}
+
+/// Блочная обработка файла потоком, результат пишется в выходной файл или stdout
+fn process_stream(
+    mode: &RunningMode,
+    input_file: &PathBuf,
+    output_file: Option<&PathBuf>,
+    keyword: &Keyword,
+    block_rows: Option<usize>,
+) -> std::io::Result<()> {
+    let input = File::open(input_file)?;
+    let output: Box<dyn std::io::Write> = match output_file {
+        Some(output_file) => Box::new(std::io::BufWriter::new(File::create(output_file)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    match mode {
+        RunningMode::Encrypt => match block_rows {
+            Some(block_rows) => encrypt_stream(input, output, keyword, block_rows),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "streaming encryption requires --block-rows",
+            )),
+        },
+        RunningMode::Decrypt => decrypt_stream(input, output, keyword, block_rows),
+    }
+}
+
+fn write_result<T: AsRef<[u8]>>(content: &T, filename: Option<PathBuf>) -> Option<()> {
+    use std::io::Write;
+    let mut file = File::create(filename?).ok()?;
+    file.write_all(content.as_ref()).ok()?;
+    Some(())
+}
+
+fn read_content(filename: &PathBuf) -> Option<String> {
+    let content = fs::read_to_string(filename).ok()?;
+    Some(content.strip_suffix('\n').unwrap_or(&content).to_string())
+}
+
+fn attack(args: AttackArgs) -> ExitCode {
+    if args.min_key == 0 || args.min_key > args.max_key {
+        error!("Key length range must be positive and non-empty");
+        return ExitCode::from(1);
+    }
+    let model = match &args.corpus {
+        Some(corpus) => match fs::read_to_string(corpus) {
+            Ok(corpus) => LanguageModel::from_corpus(&corpus),
+            Err(e) => {
+                error!("Failed to read corpus {}: {e}", corpus.display());
+                return ExitCode::from(1);
+            }
+        },
+        None => LanguageModel::default(),
+    };
+    let mut ciphertexts = Vec::new();
+    for filename in &args.ciphertexts {
+        match read_content(filename) {
+            Some(content) => ciphertexts.push(content),
+            None => {
+                error!("Failed to read file {}", filename.display());
+                return ExitCode::from(1);
+            }
+        }
+    }
+    let Some(recovered) = multiple_anagram(&ciphertexts, args.min_key..=args.max_key, &model)
+    else {
+        error!("No key lengths to check");
+        return ExitCode::from(1);
+    };
+    println!("Key length: {}", recovered.ranks.len());
+    println!("Keyword: {}", recovered.keyword());
+    println!("Score: {:.4}", recovered.score);
+    for (filename, plaintext) in args.ciphertexts.iter().zip(&recovered.plaintexts) {
+        println!("{}:\n{plaintext}", filename.display());
+    }
+    ExitCode::SUCCESS
+}
+
+fn recover(args: RecoverKeyArgs) -> ExitCode {
+    let mut pairs = Vec::new();
+    for files in args.pair.chunks(2) {
+        match (read_content(&files[0]), read_content(&files[1])) {
+            (Some(ciphertext), Some(plaintext)) => {
+                pairs.push(KnownPair::new(&ciphertext, &plaintext))
+            }
+            _ => {
+                error!(
+                    "Failed to read pair {} {}",
+                    files[0].display(),
+                    files[1].display()
+                );
+                return ExitCode::from(1);
+            }
+        }
+    }
+    let Some(key) = recover_key(&pairs, args.min_key..=args.max_key) else {
+        error!("No key reproduces the ciphertexts from the known plaintexts");
+        return ExitCode::from(1);
+    };
+    if key.candidates > 1 {
+        warn!(
+            "Known plaintext is consistent with {} keys, showing the first",
+            key.candidates
+        );
+    }
+    println!("Key length: {}", key.ranks.len());
+    println!("Keyword: {}", key.keyword());
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Диагностика пишется в stderr, stdout остается только для результата
+    env_logger::Builder::new()
+        .filter_level(cli.verbosity.log_level_filter())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
+    match (cli.command, cli.args) {
+        (Some(Command::Attack(args)), _) => attack(args),
+        (Some(Command::RecoverKey(args)), _) => recover(args),
+        (None, Some(args)) => transposition(args),
+        (None, None) => {
+            error!("Missing arguments, see --help");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn transposition(args: Args) -> ExitCode {
+    if !check_keyword(&args.keyword) {
+        error!(
+            "Keyword '{}' is empty or contain unexpected symbols",
+            args.keyword
+        );
+        return ExitCode::from(1);
+    }
+    let second_keyword = match (&args.cipher, args.second_keyword) {
+        (CipherKind::Double, None) => {
+            error!("Double transposition requires --second-keyword");
+            return ExitCode::from(1);
+        }
+        (_, Some(keyword)) if !check_keyword(&keyword) => {
+            error!("Keyword '{keyword}' is empty or contain unexpected symbols");
+            return ExitCode::from(1);
+        }
+        (_, keyword) => keyword.unwrap_or_default(),
+    };
+    let keyword = Keyword::new(&args.keyword, args.collation);
+    let second_keyword = Keyword::new(&second_keyword, args.collation);
+    debug!(
+        "{:?} cipher, keyword of {} columns",
+        args.cipher,
+        keyword.len()
+    );
+    let width = args.width.unwrap_or_else(|| keyword.len());
+    if width == 0 {
+        error!("Width must be positive");
+        return ExitCode::from(1);
+    }
+    if args.stream || args.block_rows.is_some() {
+        if !matches!(args.cipher, CipherKind::Columnar) || args.block_rows == Some(0) {
+            error!("--block-rows must be positive and is supported only by columnar cipher");
+            return ExitCode::from(1);
+        }
+        if args.fill.is_some()
+            || args.bytes
+            || args.graphemes
+            || args.show_grid
+            || args.trace
+            || args.normalization.is_some()
+        {
+            error!("--stream and --block-rows are supported only by plain columnar cipher");
+            return ExitCode::from(1);
+        }
+        let output_file = args.output_file.as_ref();
+        let processed = process_stream(
+            &args.running_mode,
+            &args.input_file,
+            output_file,
+            &keyword,
+            args.block_rows,
+        );
+        return match processed {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("Failed to process stream {e}");
+                ExitCode::from(1)
+            }
+        };
+    }
+    if args.bytes {
+        if !matches!(args.cipher, CipherKind::Columnar)
+            || args.fill.is_some()
+            || args.show_grid
+            || args.trace
+        {
+            error!("--bytes is supported only by plain columnar cipher");
+            return ExitCode::from(1);
+        }
+        let content = match fs::read(&args.input_file) {
+            Ok(content) => content,
+            Err(e) => {
+                error!("Failed to read input file {e}");
+                return ExitCode::from(1);
+            }
+        };
+        if matches!(args.running_mode, RunningMode::Decrypt) && is_block_ciphertext(&content) {
+            error!("Input is a block ciphertext, decrypt it with --stream");
+            return ExitCode::from(1);
+        }
+        let new_content = match args.running_mode {
+            RunningMode::Encrypt => {
+                let mut encrypt = Cipher::with_keyword(keyword);
+                content.into_iter().for_each(|b| encrypt.add_symbol(b));
+                encrypt.encrypt_symbols()
+            }
+            RunningMode::Decrypt => Cipher::from_symbols(content, keyword).decrypt_symbols(),
+        };
+        if write_result(&new_content, args.output_file).is_none() {
+            use std::io::Write;
+            if let Err(e) = std::io::stdout().write_all(&new_content) {
+                error!("Failed to write result {e}");
+                return ExitCode::from(1);
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+    // Чтение файла
+    let content = match fs::read_to_string(&args.input_file) {
+        Ok(content) => content.strip_suffix("\n").unwrap_or(&content).to_string(),
+        Err(e) => {
+            error!("Failed to read input file {e}");
+            return ExitCode::from(1);
+        }
+    };
+    if matches!(args.running_mode, RunningMode::Decrypt) && is_block_ciphertext(content.as_bytes())
+    {
+        error!("Input is a block ciphertext, decrypt it with --stream");
+        return ExitCode::from(1);
+    }
+    let content = match (&args.running_mode, args.normalization) {
+        (RunningMode::Encrypt, Some(normalization)) => normalize(&content, normalization),
+        _ => content,
+    };
+
+    if args.graphemes {
+        if !matches!(args.cipher, CipherKind::Columnar)
+            || args.fill.is_some()
+            || args.show_grid
+            || args.trace
+        {
+            error!("--graphemes is supported only by plain columnar cipher");
+            return ExitCode::from(1);
+        }
+        let new_content = match args.running_mode {
+            RunningMode::Encrypt => match encrypt_graphemes(&content, keyword) {
+                Some(encrypted) => encrypted,
+                None => {
+                    error!("Grapheme clusters of the input would merge after transposition");
+                    return ExitCode::from(1);
+                }
+            },
+            RunningMode::Decrypt => decrypt_graphemes(&content, keyword),
+        };
+        if write_result(&new_content, args.output_file).is_none() {
+            println!("{new_content}");
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let fill = match (&args.cipher, &args.running_mode, args.fill) {
+        (_, _, None) => None,
+        (CipherKind::Columnar, RunningMode::Encrypt, Some(None)) => match random_null(&content) {
+            Some(null) => {
+                // Без пустого символа дешифрование невозможно, поэтому он выводится
+                // в stderr независимо от уровня логирования
+                eprintln!("Null character wasn't specified, generating random: '{null}'");
+                Some(null)
+            }
+            None => {
+                error!("Failed to pick null character absent from input");
+                return ExitCode::from(1);
+            }
+        },
+        (CipherKind::Columnar, RunningMode::Decrypt, Some(None)) => {
+            error!("Decryption with --fill requires the null character used for encryption");
+            return ExitCode::from(1);
+        }
+        (CipherKind::Columnar, RunningMode::Encrypt, Some(Some(null))) => {
+            // Пустые символы в конце текста неотличимы от заполнения и пропали бы при дешифровании
+            if content.ends_with(null) {
+                error!("Plaintext ends with null character '{null}', choose another one");
+                return ExitCode::from(1);
+            }
+            if content.contains(null) {
+                warn!("Null character '{null}' occurs in plaintext");
+            }
+            Some(null)
+        }
+        (CipherKind::Columnar, _, Some(null)) => null,
+        (_, _, Some(_)) => {
+            error!("--fill is supported only by columnar cipher");
+            return ExitCode::from(1);
+        }
+    };
+
+    let (show_grid, trace) = (args.show_grid, args.trace);
+    if (show_grid || trace) && !matches!(args.cipher, CipherKind::Columnar) {
+        error!("--show-grid and --trace are supported only by columnar cipher");
+        return ExitCode::from(1);
+    }
+    if trace && matches!(args.running_mode, RunningMode::Encrypt) {
+        error!("--trace is available only for decryption");
+        return ExitCode::from(1);
+    }
+
+    let new_content = match (args.cipher, args.running_mode, fill) {
+        (CipherKind::Columnar, RunningMode::Encrypt, Some(null)) => {
+            let mut encrypt = Cipher::new(keyword);
+            let _ = encrypt.write_str(&content);
+            encrypt.fill(null);
+            show_cipher(&encrypt, show_grid, trace);
+            encrypt.encrypt()
+        }
+        (CipherKind::Columnar, RunningMode::Decrypt, Some(null)) => {
+            match Cipher::from_filled_content(content, keyword) {
+                Some(decrypt) => {
+                    show_cipher(&decrypt, show_grid, trace);
+                    decrypt.decrypt_filled(null)
+                }
+                None => {
+                    error!("Ciphertext length isn't a multiple of keyword length");
+                    return ExitCode::from(1);
+                }
+            }
+        }
+        (CipherKind::Columnar, RunningMode::Encrypt, None) => {
+            let mut encrypt = Cipher::new(keyword);
+            let _ = encrypt.write_str(&content);
+            show_cipher(&encrypt, show_grid, trace);
+            encrypt.encrypt()
+        }
+        (CipherKind::Columnar, RunningMode::Decrypt, None) => {
+            let decrypt = Cipher::from_content(content, keyword);
+            show_cipher(&decrypt, show_grid, trace);
+            decrypt.decrypt()
+        }
+        (CipherKind::Myszkowski, RunningMode::Encrypt, _) => {
+            let mut encrypt = Myszkowski::new(keyword);
+            let _ = encrypt.write_str(&content);
+            encrypt.encrypt()
+        }
+        (CipherKind::Myszkowski, RunningMode::Decrypt, _) => {
+            let decrypt = Myszkowski::from_content(content, keyword);
+            decrypt.decrypt()
+        }
+        (CipherKind::Double, RunningMode::Encrypt, _) => {
+            let mut encrypt = DoubleCipher::new(keyword, second_keyword);
+            let _ = encrypt.write_str(&content);
+            encrypt.encrypt()
+        }
+        (CipherKind::Double, RunningMode::Decrypt, _) => {
+            let decrypt = DoubleCipher::from_content(content, keyword, second_keyword);
+            decrypt.decrypt()
+        }
+        (CipherKind::RailFence, mode, _) => {
+            transpose(RailFence::new(width, args.offset), mode, content)
+        }
+        (CipherKind::SpiralClockwise, mode, _) => {
+            transpose(Spiral::new(width, Direction::Clockwise), mode, content)
+        }
+        (CipherKind::SpiralCounterClockwise, mode, _) => transpose(
+            Spiral::new(width, Direction::CounterClockwise),
+            mode,
+            content,
+        ),
+        (CipherKind::Snake, mode, _) => transpose(Snake::new(width), mode, content),
+        (CipherKind::Diagonal, mode, _) => transpose(Diagonal::new(width), mode, content),
+        (CipherKind::Disrupted, mode, _) => transpose(Disrupted::new(keyword), mode, content),
+        (kind @ (CipherKind::Adfgx | CipherKind::Adfgvx | CipherKind::AdfgvxCyrillic), mode, _) => {
+            let alphabet = match kind {
+                CipherKind::Adfgx => Alphabet::Latin5,
+                CipherKind::Adfgvx => Alphabet::Latin6,
+                _ => Alphabet::Cyrillic6,
+            };
+            let square = Square::new(alphabet, &args.square_keyword);
+            match mode {
+                RunningMode::Encrypt => {
+                    let mut encrypt = Adfgvx::new(square, keyword);
+                    let _ = encrypt.write_str(&content);
+                    encrypt.encrypt()
+                }
+                RunningMode::Decrypt => {
+                    match Adfgvx::from_content(content, square, keyword).decrypt() {
+                        Some(decrypted) => decrypted,
+                        None => {
+                            error!("Ciphertext isn't a valid {kind:?} message");
+                            return ExitCode::from(1);
+                        }
+                    }
+                }
+            }
+        }
+    };
+    if write_result(&new_content, args.output_file).is_none() {
+        println!("{new_content}");
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collation::Collation;
+    use crate::{parse_keyword, Cipher, DoubleCipher, KeySymbol, Keyword, Myszkowski};
+    use std::fmt::Write;
+
+    #[test]
+    fn complex_test() {
+        let initial_text: String = String::from("перестановочный шифр");
+        let keyword: String = String::from("шифр");
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text);
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, String::from("етвыиенч рраойфпсонш"));
+        let decrypt = Cipher::from_content(encrypted, keyword);
+        let decrypted = decrypt.decrypt();
+        assert_eq!(decrypted, initial_text);
+    }
+
+    #[test]
+    fn complex_test2() {
+        let initial_text: String = String::from("перест");
+        println!("{}", initial_text);
+        let keyword: String = String::from("шифр");
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text);
+        println!("{:#?}", encrypt);
+        let encrypted = encrypt.encrypt();
+        println!("{}", encrypted);
+        let decrypt = Cipher::from_content(encrypted, keyword);
+        println!("{:#?}", decrypt);
+        let decrypted = decrypt.decrypt();
+        println!("{}", decrypted);
+        assert_eq!(decrypted, initial_text);
+    }
+
+    #[test]
+    fn complex_test3() {
+        let initial_text: String = String::from("Трус умирает каждый день, а воин ожидает свою гибель и живет каждый день, если человек ушел из жизни раньше времени, то он обретает вечное существование, но правила таковы, нельзя убивать самого себя и спровоцировать свою гибель, и поэтому в нас заложены самосохранение, интуиция и инстинкт.");
+        println!("{}", initial_text);
+        let keyword: String = String::from("шифр");
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text);
+        println!("{:#?}", encrypt);
+        let encrypted = encrypt.encrypt();
+        println!("{}", encrypted);
+        let decrypt = Cipher::from_content(encrypted, keyword);
+        println!("{:#?}", decrypt);
+        let decrypted = decrypt.decrypt();
+        println!("{}", decrypted);
+        assert_eq!(decrypted, initial_text);
+    }
+
+    #[test]
+    fn repeated_letters_keyword() {
+        let initial_text: String = String::from("WEAREDISCOVERED");
+        let keyword: String = String::from("SECRET");
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text);
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, String::from("ACDESEEVROWIRDE"));
+        let decrypt = Cipher::from_content(encrypted, keyword);
+        assert_eq!(decrypt.decrypt(), initial_text);
+
+        let initial_text: String = String::from("перестановочный шифр с повторами");
+        let keyword: String = String::from("ключик");
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text);
+        let decrypt = Cipher::from_content(encrypt.encrypt(), keyword);
+        assert_eq!(decrypt.decrypt(), initial_text);
+    }
+
+    #[test]
+    fn numeric_keyword() {
+        assert_eq!(
+            parse_keyword("3, 1 12-2", Collation::Codepoint),
+            vec![
+                KeySymbol::Number(3),
+                KeySymbol::Number(1),
+                KeySymbol::Number(12),
+                KeySymbol::Number(2)
+            ]
+        );
+        let initial_text: String = String::from("WEAREDISCOVERED");
+        let mut encrypt = Cipher::new(String::from("5,2,1,4,3,6"));
+        let _ = encrypt.write_str(&initial_text);
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, String::from("ACDESEEVROWIRDE"));
+        let decrypt = Cipher::from_content(encrypted, String::from("5 2 1 4 3 6"));
+        assert_eq!(decrypt.decrypt(), initial_text);
+    }
+
+    #[test]
+    fn myszkowski_test() {
+        let initial_text: String = String::from("WEAREDISCOVEREDFLEEATONCE");
+        let keyword: String = String::from("TOMATO");
+        let mut encrypt = Myszkowski::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text);
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, String::from("ROFOACDTEDSEEEACWEIVRLENE"));
+        let decrypt = Myszkowski::from_content(encrypted, keyword);
+        assert_eq!(decrypt.decrypt(), initial_text);
+    }
+
+    #[test]
+    fn myszkowski_complex_test() {
+        let initial_text: String = String::from("Трус умирает каждый день, а воин ожидает свою гибель и живет каждый день, если человек ушел из жизни раньше времени, то он обретает вечное существование, но правила таковы, нельзя убивать самого себя и спровоцировать свою гибель, и поэтому в нас заложены самосохранение, интуиция и инстинкт.");
+        for keyword in ["шифр", "ключик", "аббревиатура", "3,1,3,2"] {
+            let mut encrypt = Myszkowski::new(keyword.to_string());
+            let _ = encrypt.write_str(&initial_text);
+            let encrypted = encrypt.encrypt();
+            assert_ne!(encrypted, initial_text);
+            let decrypt = Myszkowski::from_content(encrypted, keyword.to_string());
+            assert_eq!(decrypt.decrypt(), initial_text);
+        }
+    }
+
+    #[test]
+    fn double_transposition_test() {
+        let initial_text: String = String::from("WEAREDISCOVEREDFLEEATONCE");
+        let mut encrypt = DoubleCipher::new(String::from("ZEBRAS"), String::from("STRIPE"));
+        let _ = encrypt.write_str(&initial_text);
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted, String::from("CAEENSOIAEDRLEFWEDREEVTOC"));
+        let decrypt =
+            DoubleCipher::from_content(encrypted, String::from("ZEBRAS"), String::from("STRIPE"));
+        assert_eq!(decrypt.decrypt(), initial_text);
+    }
+
+    #[test]
+    fn double_transposition_irregular_grid() {
+        let text: Vec<char> = "перестановочный шифр с двумя ключами".chars().collect();
+        for len in 0..=text.len() {
+            let initial_text: String = text[..len].iter().collect();
+            let mut encrypt = DoubleCipher::new(String::from("шифр"), String::from("ключик"));
+            let _ = encrypt.write_str(&initial_text);
+            let decrypt = DoubleCipher::from_content(
+                encrypt.encrypt(),
+                String::from("шифр"),
+                String::from("ключик"),
+            );
+            assert_eq!(decrypt.decrypt(), initial_text);
+        }
+    }
+
+    #[test]
+    fn filled_grid_test() {
+        let initial_text: String = String::from("перестановочный шифр");
+        let keyword: String = String::from("ключ");
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&initial_text[..initial_text.len() - 2]);
+        encrypt.fill('ъ');
+        let encrypted = encrypt.encrypt();
+        assert_eq!(encrypted.chars().count(), 20);
+        assert_eq!(encrypted.chars().filter(|c| *c == 'ъ').count(), 1);
+        let decrypt = Cipher::from_filled_content(encrypted.clone(), keyword.clone()).unwrap();
+        assert_eq!(decrypt.decrypt_filled('ъ'), "перестановочный шиф");
+        assert!(Cipher::from_filled_content(encrypted[2..].to_string(), keyword).is_none());
+    }
+
+    #[test]
+    fn byte_transposition_test() {
+        let initial_bytes: Vec<u8> = (0..=255u8).rev().chain([0xd0, 0x0a, 0xff, b'\n']).collect();
+        for keyword in ["шифр", "ключик", "3,1,2"] {
+            let mut encrypt = Cipher::with_keyword(keyword.to_string());
+            initial_bytes.iter().for_each(|b| encrypt.add_symbol(*b));
+            let encrypted = encrypt.encrypt_symbols();
+            assert_ne!(encrypted, initial_bytes);
+            let decrypt = Cipher::from_symbols(encrypted, keyword.to_string());
+            assert_eq!(decrypt.decrypt_symbols(), initial_bytes);
+        }
+        let text = "перестановочный шифр\n";
+        let mut encrypt = Cipher::with_keyword(String::from("шифр"));
+        text.bytes().for_each(|b| encrypt.add_symbol(b));
+        let decrypt = Cipher::from_symbols(encrypt.encrypt_symbols(), String::from("шифр"));
+        assert_eq!(String::from_utf8(decrypt.decrypt_symbols()).unwrap(), text);
+    }
+
+    #[test]
+    fn collation_test() {
+        let initial_text: String = String::from("перестановка");
+        // По кодам символов ё следует за я, по русскому алфавиту - сразу за е
+        let mut codepoint = Cipher::new(Keyword::new("ёжя", Collation::Codepoint));
+        let _ = codepoint.write_str(&initial_text);
+        assert_eq!(codepoint.encrypt(), "еснкртоапеав");
+        let mut russian = Cipher::new(Keyword::new("ёжя", Collation::Russian));
+        let _ = russian.write_str(&initial_text);
+        let encrypted = russian.encrypt();
+        assert_eq!(encrypted, "пеавеснкртоа");
+        let decrypt = Cipher::from_content(encrypted, Keyword::new("ЁЖЯ", Collation::Russian));
+        assert_eq!(decrypt.decrypt(), initial_text);
+
+        // Без учета регистра одинаковые буквы разного регистра упорядочиваются слева направо
+        let mut encrypt = Cipher::new(Keyword::new("Bab", Collation::CaseInsensitive));
+        let _ = encrypt.write_str("123456");
+        assert_eq!(encrypt.encrypt(), "251436");
+        let mut encrypt = Myszkowski::new(Keyword::new("Bab", Collation::CaseInsensitive));
+        let _ = encrypt.write_str("123456");
+        assert_eq!(encrypt.encrypt(), "251346");
+    }
+}
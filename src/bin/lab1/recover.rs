@@ -0,0 +1,263 @@
+use crate::attack::{ranks_keyword, ranks_of};
+use crate::{column_lengths, Cipher, KeySymbol, Keyword};
+use log::debug;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+/// Наибольшее число различных ключей, собираемых для одной длины ключа
+const SOLUTIONS_LIMIT: usize = 16;
+
+/// Известная пара: шифротекст и открытый текст целиком или его фрагмент с неизвестным смещением
+#[derive(Debug)]
+pub struct KnownPair {
+    ciphertext: Vec<char>,
+    plaintext: Vec<char>,
+}
+
+impl KnownPair {
+    pub fn new(ciphertext: &str, plaintext: &str) -> Self {
+        Self {
+            ciphertext: ciphertext.chars().collect(),
+            plaintext: plaintext.chars().collect(),
+        }
+    }
+
+    /// Возможные смещения фрагмента открытого текста, для полного текста только нулевое
+    fn offsets(&self) -> Vec<usize> {
+        match self.ciphertext.len().checked_sub(self.plaintext.len()) {
+            Some(slack) => (0..=slack).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Известные символы столбца `column` при смещении фрагмента `offset`: строка и символ
+    fn column_cells(&self, offset: usize, column: usize, columns: usize) -> Vec<(usize, char)> {
+        self.plaintext
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (offset + i, *c))
+            .filter(|(position, _)| position % columns == column)
+            .map(|(position, c)| (position / columns, c))
+            .collect()
+    }
+}
+
+/// Состояние перебора для одной пары: начало следующего столбца в шифротексте,
+/// длины столбцов и смещения фрагмента, еще не противоречащие выбранным столбцам
+#[derive(Debug, Clone)]
+struct PairState {
+    position: usize,
+    lengths: Vec<usize>,
+    offsets: Vec<usize>,
+}
+
+/// Перебор с возвратом порядка чтения столбцов: очередной столбец должен начинаться
+/// в шифротексте каждой пары с известных символов этого столбца
+fn search(
+    pairs: &[KnownPair],
+    states: Vec<PairState>,
+    order: &mut Vec<usize>,
+    used: &mut Vec<bool>,
+    solutions: &mut Vec<Vec<usize>>,
+) {
+    let columns = used.len();
+    if solutions.len() >= SOLUTIONS_LIMIT {
+        return;
+    }
+    if order.len() == columns {
+        solutions.push(order.clone());
+        return;
+    }
+    for column in 0..columns {
+        if used[column] {
+            continue;
+        }
+        let next: Option<Vec<PairState>> = pairs
+            .iter()
+            .zip(&states)
+            .map(|(pair, state)| {
+                let offsets: Vec<usize> = state
+                    .offsets
+                    .iter()
+                    .copied()
+                    .filter(|offset| {
+                        pair.column_cells(*offset, column, columns)
+                            .into_iter()
+                            .all(|(row, c)| pair.ciphertext[state.position + row] == c)
+                    })
+                    .collect();
+                (!offsets.is_empty()).then(|| PairState {
+                    position: state.position + state.lengths[column],
+                    lengths: state.lengths.clone(),
+                    offsets,
+                })
+            })
+            .collect();
+        if let Some(next) = next {
+            used[column] = true;
+            order.push(column);
+            search(pairs, next, order, used, solutions);
+            order.pop();
+            used[column] = false;
+        }
+    }
+}
+
+/// Найденный по известным парам ключ
+#[derive(Debug)]
+pub struct KnownKey {
+    /// Номер каждого столбца в порядке чтения, начиная с 1
+    pub ranks: Vec<usize>,
+    /// Число различных ключей, совместимых с парами
+    pub candidates: usize,
+}
+
+impl KnownKey {
+    pub fn keyword(&self) -> String {
+        ranks_keyword(&self.ranks)
+    }
+}
+
+fn numeric_keyword(ranks: &[usize]) -> Keyword {
+    Keyword(ranks.iter().map(|r| KeySymbol::Number(*r as u64)).collect())
+}
+
+/// Проверка ключа повторным шифрованием. Полный открытый текст шифруется и сравнивается
+/// с шифротекстом. Для фрагмента шифруются номера позиций текста, и при одном из смещений
+/// фрагмента символы шифротекста на позициях, куда попадает фрагмент, должны с ним совпасть
+fn verify(pairs: &[KnownPair], ranks: &[usize]) -> bool {
+    pairs.iter().all(|pair| {
+        let ciphertext: String = pair.ciphertext.iter().collect();
+        if pair.plaintext.len() == pair.ciphertext.len() {
+            let mut encrypt = Cipher::new(numeric_keyword(ranks));
+            let _ = encrypt.write_str(&pair.plaintext.iter().collect::<String>());
+            return encrypt.encrypt() == ciphertext;
+        }
+        let mut positions = Cipher::with_keyword(numeric_keyword(ranks));
+        for position in 0..pair.ciphertext.len() {
+            positions.add_symbol(position);
+        }
+        let positions = positions.encrypt_symbols();
+        pair.offsets().into_iter().any(|offset| {
+            positions
+                .iter()
+                .zip(&pair.ciphertext)
+                .filter_map(|(position, c)| {
+                    let i = position.checked_sub(offset)?;
+                    pair.plaintext.get(i).map(|p| (p, c))
+                })
+                .all(|(p, c)| p == c)
+        })
+    })
+}
+
+/// Восстановление ключа столбцовой перестановки по известным парам шифротекст - открытый текст.
+/// Перебираются длины ключа, для которых фрагменты покрывают все столбцы. Ключи, дающие
+/// одинаковые открытые тексты (например, при одинаковых столбцах), считаются одним
+pub fn recover_key(pairs: &[KnownPair], key_lengths: RangeInclusive<usize>) -> Option<KnownKey> {
+    let longest = pairs.iter().map(|p| p.plaintext.len()).max()?;
+    let mut found: Vec<(Vec<usize>, Vec<String>)> = Vec::new();
+    for columns in key_lengths.filter(|k| *k > 0 && *k <= longest) {
+        let states: Vec<PairState> = pairs
+            .iter()
+            .map(|pair| PairState {
+                position: 0,
+                lengths: column_lengths(pair.ciphertext.len(), columns)
+                    .into_iter()
+                    .map(|step| step.length)
+                    .collect(),
+                offsets: pair.offsets(),
+            })
+            .collect();
+        let mut solutions = Vec::new();
+        search(
+            pairs,
+            states,
+            &mut Vec::with_capacity(columns),
+            &mut vec![false; columns],
+            &mut solutions,
+        );
+        debug!(
+            "Key length {columns}: {} column orders match",
+            solutions.len()
+        );
+        for order in solutions {
+            let ranks = ranks_of(&order);
+            if !verify(pairs, &ranks) {
+                continue;
+            }
+            let plaintexts: Vec<String> = pairs
+                .iter()
+                .map(|pair| {
+                    let ciphertext: String = pair.ciphertext.iter().collect();
+                    Cipher::from_content(ciphertext, numeric_keyword(&ranks)).decrypt()
+                })
+                .collect();
+            if !found.iter().any(|(_, texts)| *texts == plaintexts) {
+                found.push((ranks, plaintexts));
+            }
+        }
+        if !found.is_empty() {
+            break;
+        }
+    }
+    let candidates = found.len();
+    let (ranks, _) = found.into_iter().next()?;
+    Some(KnownKey { ranks, candidates })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recover_key, verify, KnownPair};
+    use crate::Cipher;
+    use std::fmt::Write;
+
+    fn encrypt(text: &str, keyword: &str) -> String {
+        let mut encrypt = Cipher::new(String::from(keyword));
+        let _ = encrypt.write_str(text);
+        encrypt.encrypt()
+    }
+
+    #[test]
+    fn full_pair_test() {
+        let plaintext = "противник сосредоточил резервы возле старой переправы";
+        let pairs = [KnownPair::new(&encrypt(plaintext, "шифровка"), plaintext)];
+        let key = recover_key(&pairs, 1..=12).unwrap();
+        assert_eq!(key.candidates, 1);
+        assert_eq!(key.keyword(), "8,3,7,6,5,2,4,1");
+        assert_eq!(
+            encrypt(plaintext, &key.keyword()),
+            encrypt(plaintext, "шифровка")
+        );
+    }
+
+    #[test]
+    fn crib_dragging_test() {
+        let first = "завтра утром отправляйте все донесения в штаб северного фронта";
+        let second = "связь с третьим полком потеряна после полуночи, ждем указаний";
+        let pairs = [
+            KnownPair::new(&encrypt(first, "ключ"), "донесения"),
+            KnownPair::new(&encrypt(second, "ключ"), "полуночи"),
+        ];
+        let key = recover_key(&pairs, 2..=8).unwrap();
+        assert_eq!(key.keyword(), "1,2,4,3");
+    }
+
+    #[test]
+    fn inconsistent_pairs() {
+        let pairs = [KnownPair::new("абвг", "гдеж")];
+        assert!(recover_key(&pairs, 1..=4).is_none());
+    }
+
+    #[test]
+    fn verify_by_encryption() {
+        let plaintext = "перенос встречи на четверг";
+        let ciphertext = encrypt(plaintext, "4,1,5,2,3");
+        let pairs = [KnownPair::new(&ciphertext, plaintext)];
+        assert!(verify(&pairs, &[4, 1, 5, 2, 3]));
+        assert!(!verify(&pairs, &[4, 1, 5, 3, 2]));
+        let cribs = [KnownPair::new(&ciphertext, "встречи")];
+        assert!(verify(&cribs, &[4, 1, 5, 2, 3]));
+        assert!(!verify(&cribs, &[1, 4, 5, 2, 3]));
+    }
+}
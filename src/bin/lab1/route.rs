@@ -0,0 +1,301 @@
+use std::fmt::Write;
+
+/// Маршрут перестановки: порядок, в котором позиции открытого текста попадают в шифротекст
+pub trait Route {
+    /// Позиции символов открытого текста длины `len` в порядке их следования в шифротексте
+    fn read_order(&self, len: usize) -> Vec<usize>;
+}
+
+/// Шифр маршрутной перестановки, повторяет интерфейс `Cipher`
+#[derive(Debug)]
+pub struct RouteCipher<R> {
+    route: R,
+    content: Vec<char>,
+}
+
+impl<R: Route> RouteCipher<R> {
+    /// Конструктор пустого шифра из маршрута
+    pub fn new(route: R) -> Self {
+        Self {
+            route,
+            content: Vec::new(),
+        }
+    }
+
+    /// Конструктор шифра из зашифрованных данных и маршрута, по которому проводилось шифрование
+    pub fn from_content(content: String, route: R) -> Self {
+        Self {
+            route,
+            content: content.chars().collect(),
+        }
+    }
+
+    pub fn encrypt(self) -> String {
+        self.route
+            .read_order(self.content.len())
+            .into_iter()
+            .map(|i| self.content[i])
+            .collect()
+    }
+
+    pub fn decrypt(self) -> String {
+        let mut ans = self.content.clone();
+        let order = self.route.read_order(self.content.len());
+        for (c, i) in self.content.iter().zip(order) {
+            ans[i] = *c;
+        }
+        ans.iter().collect()
+    }
+}
+
+impl<R> Write for RouteCipher<R> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.content.extend(s.chars());
+        Ok(())
+    }
+}
+
+/// Шифр ограждения: текст записывается зигзагом по `rails` строкам, начиная со сдвига `offset` в периоде зигзага
+#[derive(Debug)]
+pub struct RailFence {
+    rails: usize,
+    offset: usize,
+}
+
+impl RailFence {
+    pub fn new(rails: usize, offset: usize) -> Self {
+        assert!(rails > 0, "Rail fence requires at least one rail");
+        Self { rails, offset }
+    }
+
+    /// Номер строки, на которую попадает символ с заданной позицией
+    fn rail(&self, position: usize) -> usize {
+        if self.rails == 1 {
+            return 0;
+        }
+        let period = 2 * (self.rails - 1);
+        let phase = (position + self.offset) % period;
+        if phase < self.rails {
+            phase
+        } else {
+            period - phase
+        }
+    }
+}
+
+impl Route for RailFence {
+    fn read_order(&self, len: usize) -> Vec<usize> {
+        let mut rails = vec![Vec::new(); self.rails];
+        for position in 0..len {
+            rails[self.rail(position)].push(position);
+        }
+        rails.concat()
+    }
+}
+
+/// Направление обхода спирали
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Текст записывается в таблицу по строкам и читается по спирали от левого верхнего угла
+#[derive(Debug)]
+pub struct Spiral {
+    width: usize,
+    direction: Direction,
+}
+
+impl Spiral {
+    pub fn new(width: usize, direction: Direction) -> Self {
+        assert!(width > 0, "Grid width must be positive");
+        Self { width, direction }
+    }
+}
+
+/// Обход таблицы `rows` x `columns` по часовой стрелке от левого верхнего угла
+fn clockwise_cells(rows: usize, columns: usize) -> Vec<(usize, usize)> {
+    let mut cells = Vec::with_capacity(rows * columns);
+    let (mut top, mut bottom) = (0isize, rows as isize - 1);
+    let (mut left, mut right) = (0isize, columns as isize - 1);
+    while top <= bottom && left <= right {
+        for column in left..=right {
+            cells.push((top, column));
+        }
+        top += 1;
+        for row in top..=bottom {
+            cells.push((row, right));
+        }
+        right -= 1;
+        if top <= bottom {
+            for column in (left..=right).rev() {
+                cells.push((bottom, column));
+            }
+            bottom -= 1;
+        }
+        if left <= right {
+            for row in (top..=bottom).rev() {
+                cells.push((row, left));
+            }
+            left += 1;
+        }
+    }
+    cells
+        .into_iter()
+        .map(|(row, column)| (row as usize, column as usize))
+        .collect()
+}
+
+/// Позиции текста длины `len`, записанного по строкам в таблицу ширины `width`, в порядке обхода ячеек
+fn grid_order(len: usize, width: usize, cells: Vec<(usize, usize)>) -> Vec<usize> {
+    cells
+        .into_iter()
+        .map(|(row, column)| row * width + column)
+        .filter(|position| *position < len)
+        .collect()
+}
+
+impl Route for Spiral {
+    fn read_order(&self, len: usize) -> Vec<usize> {
+        let rows = len.div_ceil(self.width);
+        let cells = match self.direction {
+            Direction::Clockwise => clockwise_cells(rows, self.width),
+            // Обход против часовой стрелки - обход по часовой стрелке транспонированной таблицы
+            Direction::CounterClockwise => clockwise_cells(self.width, rows)
+                .into_iter()
+                .map(|(row, column)| (column, row))
+                .collect(),
+        };
+        grid_order(len, self.width, cells)
+    }
+}
+
+/// Бустрофедон: строки таблицы читаются попеременно слева направо и справа налево
+#[derive(Debug)]
+pub struct Snake {
+    width: usize,
+}
+
+impl Snake {
+    pub fn new(width: usize) -> Self {
+        assert!(width > 0, "Grid width must be positive");
+        Self { width }
+    }
+}
+
+impl Route for Snake {
+    fn read_order(&self, len: usize) -> Vec<usize> {
+        let rows = len.div_ceil(self.width);
+        let mut cells = Vec::with_capacity(rows * self.width);
+        for row in 0..rows {
+            if row % 2 == 0 {
+                cells.extend((0..self.width).map(|column| (row, column)));
+            } else {
+                cells.extend((0..self.width).rev().map(|column| (row, column)));
+            }
+        }
+        grid_order(len, self.width, cells)
+    }
+}
+
+/// Таблица читается по диагоналям, параллельным побочной, каждая диагональ сверху вниз
+#[derive(Debug)]
+pub struct Diagonal {
+    width: usize,
+}
+
+impl Diagonal {
+    pub fn new(width: usize) -> Self {
+        assert!(width > 0, "Grid width must be positive");
+        Self { width }
+    }
+}
+
+impl Route for Diagonal {
+    fn read_order(&self, len: usize) -> Vec<usize> {
+        let rows = len.div_ceil(self.width);
+        let mut cells = Vec::with_capacity(rows * self.width);
+        for diagonal in 0..(rows + self.width).saturating_sub(1) {
+            for row in 0..rows.min(diagonal + 1) {
+                let column = diagonal - row;
+                if column < self.width {
+                    cells.push((row, column));
+                }
+            }
+        }
+        grid_order(len, self.width, cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagonal, Direction, RailFence, Route, RouteCipher, Snake, Spiral};
+    use std::fmt::Write;
+
+    fn encrypt<R: Route>(route: R, text: &str) -> String {
+        let mut encrypt = RouteCipher::new(route);
+        let _ = encrypt.write_str(text);
+        encrypt.encrypt()
+    }
+
+    fn round_trip<R: Route>(make_route: impl Fn() -> R) {
+        let text: Vec<char> = "перестановочный шифр по маршруту".chars().collect();
+        for len in 0..=text.len() {
+            let initial_text: String = text[..len].iter().collect();
+            let encrypted = encrypt(make_route(), &initial_text);
+            let decrypt = RouteCipher::from_content(encrypted, make_route());
+            assert_eq!(decrypt.decrypt(), initial_text);
+        }
+    }
+
+    #[test]
+    fn rail_fence_test() {
+        assert_eq!(
+            encrypt(RailFence::new(3, 0), "WEAREDISCOVEREDFLEEATONCE"),
+            "WECRLTEERDSOEEFEAOCAIVDEN"
+        );
+        assert_eq!(encrypt(RailFence::new(3, 2), "ABCDEFGH"), "CGBDFHAE");
+        assert_eq!(encrypt(RailFence::new(1, 0), "ABCDEFGH"), "ABCDEFGH");
+        for rails in 1..6 {
+            for offset in 0..8 {
+                round_trip(|| RailFence::new(rails, offset));
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_test() {
+        // ABCD
+        // EFGH
+        // IJ
+        assert_eq!(
+            encrypt(Spiral::new(4, Direction::Clockwise), "ABCDEFGHIJ"),
+            "ABCDHJIEFG"
+        );
+        assert_eq!(
+            encrypt(Spiral::new(4, Direction::CounterClockwise), "ABCDEFGHIJ"),
+            "AEIJHDCBFG"
+        );
+        for width in 1..8 {
+            round_trip(|| Spiral::new(width, Direction::Clockwise));
+            round_trip(|| Spiral::new(width, Direction::CounterClockwise));
+        }
+    }
+
+    #[test]
+    fn snake_test() {
+        assert_eq!(encrypt(Snake::new(4), "ABCDEFGHIJ"), "ABCDHGFEIJ");
+        for width in 1..8 {
+            round_trip(|| Snake::new(width));
+        }
+    }
+
+    #[test]
+    fn diagonal_test() {
+        assert_eq!(encrypt(Diagonal::new(4), "ABCDEFGHIJ"), "ABECFIDGJH");
+        for width in 1..8 {
+            round_trip(|| Diagonal::new(width));
+        }
+    }
+}
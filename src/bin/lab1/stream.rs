@@ -0,0 +1,205 @@
+use crate::{Cipher, Keyword};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Заголовок блочного шифротекста, хранит число строк таблицы в блоке
+const HEADER: &str = "BLOCK";
+
+/// Чтение не более `limit` символов UTF-8 из потока, пустая строка означает конец потока
+fn read_chars<R: BufRead>(reader: &mut R, limit: usize) -> io::Result<String> {
+    let mut ans = String::new();
+    let mut bytes = [0u8; 4];
+    for _ in 0..limit {
+        if reader.read(&mut bytes[..1])? == 0 {
+            break;
+        }
+        let width = match bytes[0] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+        if width == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8"));
+        }
+        reader.read_exact(&mut bytes[1..width])?;
+        let c = std::str::from_utf8(&bytes[..width])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        ans.push_str(c);
+    }
+    Ok(ans)
+}
+
+/// Число символов в блоке из `block_rows` строк таблицы
+fn block_len(keyword: &Keyword, block_rows: usize) -> usize {
+    keyword.len() * block_rows
+}
+
+/// Число строк в блоке из строки заголовка
+fn parse_header(line: &str) -> Option<usize> {
+    line.strip_prefix(HEADER)
+        .and_then(|rows| rows.trim().parse::<usize>().ok())
+        .filter(|rows| *rows > 0)
+}
+
+/// Начинается ли содержимое файла с заголовка блочного шифротекста
+pub fn is_block_ciphertext(content: &[u8]) -> bool {
+    content
+        .split(|b| *b == b'\n')
+        .next()
+        .filter(|_| content.contains(&b'\n'))
+        .and_then(|line| std::str::from_utf8(line).ok())
+        .and_then(parse_header)
+        .is_some()
+}
+
+/// Потоковое шифрование блоками по `block_rows` строк таблицы, каждый блок шифруется
+/// отдельным `Cipher`. Последний блок может быть неполным. Размер блока записывается в заголовок
+pub fn encrypt_stream<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    keyword: &Keyword,
+    block_rows: usize,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    writeln!(writer, "{HEADER} {block_rows}")?;
+    let len = block_len(keyword, block_rows);
+    loop {
+        let block = read_chars(&mut reader, len)?;
+        if block.is_empty() {
+            break;
+        }
+        let mut encrypt = Cipher::new(keyword.clone());
+        let _ = encrypt.write_str(&block);
+        writer.write_all(encrypt.encrypt().as_bytes())?;
+    }
+    writer.flush()
+}
+
+/// Потоковое дешифрование, размер блока берется из заголовка шифротекста.
+/// Заданный размер `expected_rows` должен совпадать с заголовком
+pub fn decrypt_stream<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    keyword: &Keyword,
+    expected_rows: Option<usize>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut header = String::new();
+    reader.read_line(&mut header)?;
+    let block_rows = parse_header(header.trim_end_matches('\n'))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing block header"))?;
+    if let Some(expected) = expected_rows.filter(|rows| *rows != block_rows) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("ciphertext has blocks of {block_rows} rows, not {expected}"),
+        ));
+    }
+    let len = block_len(keyword, block_rows);
+    loop {
+        let block = read_chars(&mut reader, len)?;
+        if block.is_empty() {
+            break;
+        }
+        let decrypt = Cipher::from_content(block, keyword.clone());
+        writer.write_all(decrypt.decrypt().as_bytes())?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_stream, encrypt_stream, is_block_ciphertext};
+    use crate::{Cipher, Keyword};
+    use std::fmt::Write;
+
+    #[test]
+    fn stream_test() {
+        let initial_text = "перестановочный шифр\nблоками по несколько строк\n";
+        let mut encrypted = Vec::new();
+        encrypt_stream(
+            initial_text.as_bytes(),
+            &mut encrypted,
+            &Keyword::from("шифр"),
+            2,
+        )
+        .unwrap();
+        let encrypted = String::from_utf8(encrypted).unwrap();
+        let (header, body) = encrypted.split_once('\n').unwrap();
+        assert_eq!(header, "BLOCK 2");
+        assert!(is_block_ciphertext(encrypted.as_bytes()));
+        let mut first_block = Cipher::new(String::from("шифр"));
+        let _ = first_block.write_str("перестан");
+        assert!(body.starts_with(&first_block.encrypt()));
+
+        let mut decrypted = Vec::new();
+        decrypt_stream(
+            encrypted.as_bytes(),
+            &mut decrypted,
+            &Keyword::from("шифр"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(decrypted).unwrap(), initial_text);
+        let mut decrypted = Vec::new();
+        decrypt_stream(
+            encrypted.as_bytes(),
+            &mut decrypted,
+            &Keyword::from("шифр"),
+            Some(2),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(decrypted).unwrap(), initial_text);
+        assert!(decrypt_stream(
+            encrypted.as_bytes(),
+            &mut Vec::new(),
+            &Keyword::from("шифр"),
+            Some(5)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn stream_irregular_blocks() {
+        let text: Vec<char> = "Трус умирает каждый день, а воин ожидает свою гибель"
+            .chars()
+            .collect();
+        for block_rows in 1..5 {
+            for len in 0..=text.len() {
+                let initial_text: String = text[..len].iter().collect();
+                let mut encrypted = Vec::new();
+                encrypt_stream(
+                    initial_text.as_bytes(),
+                    &mut encrypted,
+                    &Keyword::from("ключик"),
+                    block_rows,
+                )
+                .unwrap();
+                let mut decrypted = Vec::new();
+                decrypt_stream(
+                    encrypted.as_slice(),
+                    &mut decrypted,
+                    &Keyword::from("ключик"),
+                    Some(block_rows),
+                )
+                .unwrap();
+                assert_eq!(String::from_utf8(decrypted).unwrap(), initial_text);
+            }
+        }
+    }
+
+    #[test]
+    fn stream_without_header() {
+        assert!(!is_block_ciphertext("етвыиенч".as_bytes()));
+        assert!(!is_block_ciphertext("BLOCK 2".as_bytes()));
+        let mut decrypted = Vec::new();
+        assert!(decrypt_stream(
+            "етвыиенч".as_bytes(),
+            &mut decrypted,
+            &Keyword::from("шифр"),
+            None
+        )
+        .is_err());
+    }
+}
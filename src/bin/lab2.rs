@@ -1,4 +1,6 @@
 use clap::{Parser, ValueEnum};
+use clap_verbosity_flag::{Verbosity, WarnLevel};
+use log::{debug, error};
 use rand::random;
 use std::fs;
 use std::path::PathBuf;
@@ -23,6 +25,8 @@ struct Args {
     #[arg(long)]
     cbc_iv: Option<u64>,
     output_file: Option<PathBuf>,
+    #[command(flatten)]
+    verbosity: Verbosity<WarnLevel>,
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -134,16 +138,23 @@ impl Cbc {
 
 fn main() -> ExitCode {
     let mut args = Args::parse();
+    env_logger::Builder::new()
+        .filter_level(args.verbosity.log_level_filter())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
     let content = match fs::read(&args.input_file) {
         Ok(content) => content,
         Err(e) => {
-            eprintln!("Failed to read input file {e}");
+            error!("Failed to read input file {e}");
             return ExitCode::from(1);
         }
     };
+    // Сгенерированные ключ и вектор нужны для дешифрования, поэтому выводятся
+    // в stderr независимо от уровня логирования
     if args.tea_key.is_none() {
         args.tea_key = Some(random());
-        println!(
+        eprintln!(
             "Key for TEA wasn't specified, generating random: '{}'",
             args.tea_key.unwrap()
         );
@@ -151,12 +162,13 @@ fn main() -> ExitCode {
 
     if args.cbc_iv.is_none() {
         args.cbc_iv = Some(random());
-        println!(
+        eprintln!(
             "Initialization vector for CBC wasn't specified, generating random: '{}'",
             args.cbc_iv.unwrap()
         );
     }
 
+    debug!("{:?} {} bytes", args.running_mode, content.len());
     let mut cbc = Cbc::new(args.cbc_iv.unwrap(), Tea::new(args.tea_key.unwrap()));
     let result = cbc.process_slice(&args.running_mode, content.as_slice());
     if let Some(output_file) = args.output_file {
@@ -0,0 +1,218 @@
+use crate::complexity::{height, log2, two_adic_expansion, RationalApproximation};
+use crate::config::{RegisterConfig, MAX_TAPS};
+use crate::Fcsr;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::Signed;
+
+/// Результат атаки рациональным приближением
+#[derive(Debug)]
+pub struct Recovered {
+    /// Гамма - 2-адическое разложение `p / q`
+    pub p: BigInt,
+    /// Целое связи эквивалентного регистра, `q > 0`
+    pub q: BigInt,
+    /// Число битов, после которого приближение больше не менялось
+    pub bits_needed: usize,
+    /// Регистр Фибоначчи с целым связи `q`, вырабатывающий наблюдаемую гамму
+    pub register: Option<RecoveredRegister>,
+}
+
+impl Recovered {
+    /// 2-адическая сложность наблюдаемой гаммы
+    pub fn complexity(&self) -> f64 {
+        log2(&height(&(self.p.clone(), self.q.clone())))
+    }
+
+    /// Первые `len` битов гаммы по найденной дроби
+    pub fn regenerate(&self, len: usize) -> Vec<u8> {
+        two_adic_expansion(&self.p, &self.q, len)
+    }
+}
+
+/// Восстановленный регистр
+#[derive(Debug)]
+pub struct RecoveredRegister {
+    pub config: RegisterConfig,
+    pub carry: u8,
+    /// Номер бита гаммы, с которого начинается выход регистра: 0 или длина регистра,
+    /// если начальным состоянием стали первые биты самой гаммы
+    pub offset: usize,
+}
+
+impl RecoveredRegister {
+    /// Первые `len` битов гаммы, выработанные восстановленным регистром
+    pub fn regenerate(&self, len: usize) -> Vec<u8> {
+        let mut fcsr = Fcsr::new(self.config.clone());
+        fcsr.load(self.config.state.clone(), self.carry);
+        self.config
+            .state
+            .iter()
+            .take(self.offset)
+            .copied()
+            .chain(std::iter::repeat_with(|| fcsr.next_bit()))
+            .take(len)
+            .collect()
+    }
+}
+
+/// Длина и отводы регистра Фибоначчи с целым связи `q`: `q + 1 = sum(2^i)`, отвод `T = n - i + 1`
+fn register_shape(q: &BigInt) -> Option<(usize, Vec<usize>)> {
+    let sum: BigInt = q + 1;
+    if !q.is_positive() || sum.is_odd() {
+        return None;
+    }
+    let length = (sum.bits() - 1) as usize;
+    let taps = (1..=length)
+        .filter(|i| sum.bit(*i as u64))
+        .map(|i| length - i + 1)
+        .collect();
+    Some((length, taps))
+}
+
+/// Перенос, при котором регистр с состоянием `state` вырабатывает `bits`
+fn matching_register(
+    length: usize,
+    taps: &[usize],
+    state: Vec<u8>,
+    bits: &[u8],
+) -> Option<(RegisterConfig, u8)> {
+    let config = RegisterConfig::new(length, taps.to_vec(), state).ok()?;
+    (0..=taps.len() as u8).find_map(|carry| {
+        let mut fcsr = Fcsr::new(config.clone());
+        fcsr.load(config.state.clone(), carry);
+        bits.iter()
+            .all(|bit| fcsr.next_bit() == *bit)
+            .then(|| (config.clone(), carry))
+    })
+}
+
+/// Начальное состояние и перенос регистра с целым связи `q`. Для строго периодической
+/// гаммы (`-q <= p <= 0`) предшествующие ей биты однозначно восстанавливаются сдвигом назад,
+/// иначе состоянием служат первые биты гаммы
+fn recover_register(p: &BigInt, q: &BigInt, bits: &[u8]) -> Option<RecoveredRegister> {
+    let (length, taps) = register_shape(q)?;
+    if taps.len() > MAX_TAPS {
+        return None;
+    }
+    if !p.is_positive() && *p >= -q {
+        let mut p = p.clone();
+        let mut state = vec![0; length];
+        // Бит перед гаммой a: 2a + b = (2p + bq) / q, где b выбирается так, что числитель в [-q, 0]
+        for cell in state.iter_mut().rev() {
+            p *= 2;
+            if p < -q {
+                *cell = 1;
+                p += q;
+            }
+        }
+        if let Some((config, carry)) = matching_register(length, &taps, state, bits) {
+            return Some(RecoveredRegister {
+                config,
+                carry,
+                offset: 0,
+            });
+        }
+    }
+    let state = bits.get(..length)?.to_vec();
+    let (config, carry) = matching_register(length, &taps, state, &bits[length..])?;
+    Some(RecoveredRegister {
+        config,
+        carry,
+        offset: length,
+    })
+}
+
+/// Атака Клаппера - Горески: рациональное приближение наблюдаемых битов дает целое связи
+/// и состояние регистра, если битов примерно вдвое больше 2-адической сложности гаммы
+pub fn rational_attack(bits: &[u8]) -> Recovered {
+    let mut approximation = RationalApproximation::new();
+    let mut bits_needed = 0;
+    for (i, bit) in bits.iter().enumerate() {
+        if approximation.push(*bit) {
+            bits_needed = i + 1;
+        }
+    }
+    let (mut p, mut q) = approximation.pair();
+    if q.is_negative() {
+        p = -p;
+        q = -q;
+    }
+    let register = recover_register(&p, &q, bits);
+    Recovered {
+        p,
+        q,
+        bits_needed,
+        register,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rational_attack;
+    use crate::config::{parse_register, Preset};
+    use crate::{Cipher, Fcsr};
+    use num_bigint::BigInt;
+    use num_traits::Zero;
+
+    fn output(fcsr: &mut Fcsr, len: usize) -> Vec<u8> {
+        (0..len).map(|_| fcsr.next_bit()).collect()
+    }
+
+    #[test]
+    fn single_register() {
+        let config = parse_register("6:1,2,5,6:101100").unwrap();
+        let bits = output(&mut Fcsr::new(config.clone()), 300);
+        let recovered = rational_attack(&bits[..32]);
+        assert_eq!(recovered.q, BigInt::from(101));
+        let register = recovered.register.as_ref().unwrap();
+        assert_eq!(register.config.length, 6);
+        assert_eq!(register.config.taps, vec![6, 5, 2, 1]);
+        assert_eq!(register.regenerate(300), bits);
+        assert_eq!(recovered.regenerate(300), bits);
+    }
+
+    #[test]
+    fn variant7_register() {
+        for (i, config) in Preset::Variant7.registers().into_iter().enumerate() {
+            let bits = output(&mut Fcsr::new(config.clone()), 1000);
+            let recovered = rational_attack(&bits[..400]);
+            // Дробь выхода может сократиться, тогда найденное q - делитель целого связи
+            assert!((config.connection_integer() % &recovered.q).is_zero());
+            assert!(recovered.bits_needed <= 2 * 96 + 8);
+            let register = recovered.register.unwrap();
+            assert_eq!(register.regenerate(1000), bits);
+            if i == 0 {
+                assert_eq!(recovered.q, config.connection_integer());
+                // Без отвода 1 хватает регистра на бит короче с отводами, сдвинутыми на единицу
+                assert_eq!(register.config.length, 95);
+                let taps: Vec<usize> = config.taps.iter().map(|tap| tap - 1).collect();
+                assert_eq!(register.config.taps, taps);
+            }
+        }
+    }
+
+    #[test]
+    fn combined_registers() {
+        let registers: Vec<_> = ["5:5,3:10110", "7:7,1"]
+            .into_iter()
+            .map(|s| parse_register(s).unwrap())
+            .collect();
+        let mut cipher = Cipher::from_registers(registers);
+        let bits: Vec<u8> = (0..600).map(|_| cipher.generate_gamma() as u8).collect();
+        let recovered = rational_attack(&bits[..200]);
+        assert!(recovered.bits_needed < 100);
+        assert_eq!(recovered.regenerate(600), bits);
+        assert_eq!(recovered.register.unwrap().regenerate(600), bits);
+    }
+
+    #[test]
+    fn variant7_cipher_resists() {
+        let mut cipher = Cipher::new();
+        let bits: Vec<u8> = (0..600).map(|_| cipher.generate_gamma() as u8).collect();
+        let recovered = rational_attack(&bits[..300]);
+        // Приближение меняется до конца наблюдений и не предсказывает следующие биты
+        assert!(recovered.bits_needed > 250);
+        assert_ne!(recovered.regenerate(600), bits);
+    }
+}
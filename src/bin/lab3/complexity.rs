@@ -0,0 +1,329 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Профиль линейной сложности по алгоритму Берлекэмпа - Мэсси: элемент `i` равен
+/// длине кратчайшего LFSR, вырабатывающего первые `i + 1` битов
+pub fn linear_complexity_profile(bits: &[u8]) -> Vec<usize> {
+    let n = bits.len();
+    let (mut c, mut b) = (vec![0u8; n + 1], vec![0u8; n + 1]);
+    c[0] = 1;
+    b[0] = 1;
+    let (mut l, mut m) = (0usize, 1usize);
+    let mut profile = Vec::with_capacity(n);
+    for i in 0..n {
+        let discrepancy = (1..=l).fold(bits[i], |d, j| d ^ (c[j] & bits[i - j]));
+        if discrepancy == 0 {
+            m += 1;
+        } else if 2 * l <= i {
+            let previous = c.clone();
+            for j in m..=n {
+                c[j] ^= b[j - m];
+            }
+            l = i + 1 - l;
+            b = previous;
+            m = 1;
+        } else {
+            for j in m..=n {
+                c[j] ^= b[j - m];
+            }
+            m += 1;
+        }
+        profile.push(l);
+    }
+    profile
+}
+
+/// Высота пары `max(|x|, |y|)`
+pub fn height(pair: &(BigInt, BigInt)) -> BigInt {
+    pair.0.abs().max(pair.1.abs())
+}
+
+/// Нечетное `d`, минимизирующее высоту `f + d * g`. Высота выпукла по `d`, поэтому
+/// минимум лежит рядом с одним из изломов: нулями компонент или точками их равенства по модулю
+fn best_odd_multiplier(f: &(BigInt, BigInt), g: &(BigInt, BigInt)) -> BigInt {
+    let mut critical = Vec::new();
+    for (num, den) in [
+        (-&f.0, g.0.clone()),
+        (-&f.1, g.1.clone()),
+        (-(&f.0 + &f.1), &g.0 + &g.1),
+        (&f.1 - &f.0, &g.0 - &g.1),
+    ] {
+        if !den.is_zero() {
+            critical.push(num.div_floor(&den));
+        }
+    }
+    if critical.is_empty() {
+        critical.push(BigInt::zero());
+    }
+    critical
+        .into_iter()
+        .flat_map(|c| [&c - 1, c.clone(), &c + 1, &c + 2])
+        .filter(|d| d.is_odd())
+        .min_by_key(|d| height(&(&f.0 + d * &g.0, &f.1 + d * &g.1)))
+        .expect("odd candidates")
+}
+
+/// Рациональное приближение Клаппера - Горески, уточняемое по одному биту: пара `(p, q)`
+/// минимальной высоты, для которой `q * a - p` делится на `2^k`, где `a` - 2-адическое
+/// число из `k` полученных битов
+pub struct RationalApproximation {
+    /// Число полученных битов
+    k: usize,
+    alpha: BigInt,
+    f: (BigInt, BigInt),
+    /// Текущее приближение, `None` пока приходят только нули
+    g: Option<(BigInt, BigInt)>,
+}
+
+impl RationalApproximation {
+    pub fn new() -> Self {
+        Self {
+            k: 0,
+            alpha: BigInt::zero(),
+            f: (BigInt::zero(), BigInt::from(2)),
+            g: None,
+        }
+    }
+
+    /// Учет очередного бита, `true` если приближение изменилось
+    pub fn push(&mut self, bit: u8) -> bool {
+        let k = self.k;
+        self.k += 1;
+        if bit == 1 {
+            self.alpha += BigInt::one() << k;
+        }
+        let Some(g) = &self.g else {
+            if bit == 1 {
+                self.g = Some((self.alpha.clone(), BigInt::one()));
+            }
+            return bit == 1;
+        };
+        let f = &self.f;
+        let modulus = BigInt::one() << (k + 1);
+        if (&self.alpha * &g.1 - &g.0).mod_floor(&modulus).is_zero() {
+            self.f = (&f.0 * 2, &f.1 * 2);
+            return false;
+        }
+        let (next_g, next_f) = if height(g) < height(f) {
+            let d = best_odd_multiplier(f, g);
+            ((&f.0 + &d * &g.0, &f.1 + &d * &g.1), (&g.0 * 2, &g.1 * 2))
+        } else {
+            let d = best_odd_multiplier(g, f);
+            ((&g.0 + &d * &f.0, &g.1 + &d * &f.1), (&f.0 * 2, &f.1 * 2))
+        };
+        self.g = Some(next_g);
+        self.f = next_f;
+        true
+    }
+
+    /// Текущее приближение, для нулевой последовательности `(0, 1)`
+    pub fn pair(&self) -> (BigInt, BigInt) {
+        self.g
+            .clone()
+            .unwrap_or_else(|| (BigInt::zero(), BigInt::one()))
+    }
+}
+
+/// Рациональное приближение всей последовательности `bits`
+pub fn rational_approximation(bits: &[u8]) -> (BigInt, BigInt) {
+    let mut approximation = RationalApproximation::new();
+    for bit in bits {
+        approximation.push(*bit);
+    }
+    approximation.pair()
+}
+
+/// 2-адическая сложность префикса: двоичный логарифм высоты рационального приближения
+pub fn two_adic_complexity(bits: &[u8]) -> f64 {
+    log2(&height(&rational_approximation(bits)))
+}
+
+/// Двоичный логарифм положительного целого
+pub fn log2(h: &BigInt) -> f64 {
+    let bits = h.bits();
+    // Старшие 53 бита достаточны для логарифма в f64
+    let shift = bits.saturating_sub(53);
+    let mantissa = (h >> shift).to_f64().unwrap_or(1.0);
+    mantissa.log2() + shift as f64
+}
+
+/// Первые `len` битов 2-адического разложения `p / q` при нечетном `q`
+pub fn two_adic_expansion(p: &BigInt, q: &BigInt, len: usize) -> Vec<u8> {
+    let mut x = p.clone();
+    (0..len)
+        .map(|_| {
+            let bit = if x.is_odd() { 1 } else { 0 };
+            if bit == 1 {
+                x -= q;
+            }
+            x >>= 1;
+            bit
+        })
+        .collect()
+}
+
+/// Основания для вероятностной проверки простоты
+const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Тест Миллера - Рабина с фиксированными основаниями
+pub fn is_prime(n: &BigInt) -> bool {
+    let two = BigInt::from(2);
+    if n < &two {
+        return false;
+    }
+    for p in WITNESSES {
+        let p = BigInt::from(p);
+        if n == &p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+    let n_minus_one: BigInt = n - 1;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    'witness: for a in WITNESSES {
+        let mut x = BigInt::from(a).modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Нетривиальный делитель составного `n` методом Полларда
+fn pollard_rho(n: &BigInt) -> BigInt {
+    if n.is_even() {
+        return BigInt::from(2);
+    }
+    let mut c = BigInt::one();
+    loop {
+        let f = |x: &BigInt| (x * x + &c) % n;
+        let (mut x, mut y, mut d) = (BigInt::from(2), BigInt::from(2), BigInt::one());
+        while d.is_one() {
+            x = f(&x);
+            y = f(&f(&y));
+            d = (&x - &y).abs().gcd(n);
+        }
+        if &d != n {
+            return d;
+        }
+        c += 1;
+    }
+}
+
+/// Простые делители `n` без повторов
+pub fn prime_factors(n: &BigInt) -> Vec<BigInt> {
+    let mut factors = Vec::new();
+    let mut stack = vec![n.clone()];
+    while let Some(m) = stack.pop() {
+        if m.is_one() {
+            continue;
+        }
+        if is_prime(&m) {
+            factors.push(m);
+            continue;
+        }
+        let d = pollard_rho(&m);
+        stack.push(&m / &d);
+        stack.push(d);
+    }
+    factors.sort();
+    factors.dedup();
+    factors
+}
+
+/// Регистр с целым связи `q` вырабатывает l-последовательность (период `q - 1`),
+/// если `q` простое и 2 - первообразный корень по модулю `q`
+pub fn is_l_sequence(q: &BigInt) -> bool {
+    let two = BigInt::from(2);
+    if q <= &two || !is_prime(q) {
+        return false;
+    }
+    let order: BigInt = q - 1;
+    prime_factors(&order)
+        .iter()
+        .all(|p| !two.modpow(&(&order / p), q).is_one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_register, Preset};
+    use crate::Fcsr;
+
+    #[test]
+    fn berlekamp_massey_test() {
+        // LFSR x^4 + x + 1: s[i] = s[i - 3] ^ s[i - 4]
+        let mut bits = vec![1u8, 0, 0, 0];
+        for i in 4..30 {
+            bits.push(bits[i - 3] ^ bits[i - 4]);
+        }
+        let profile = linear_complexity_profile(&bits);
+        assert_eq!(profile[0], 1);
+        assert_eq!(*profile.last().unwrap(), 4);
+        assert_eq!(linear_complexity_profile(&[0, 0, 1]), vec![0, 0, 3]);
+    }
+
+    #[test]
+    fn rational_approximation_test() {
+        // -1/3 = ...010101 в 2-адической записи: 1, 0, 1, 0, ...
+        let bits: Vec<u8> = (0..20).map(|i| (i % 2 == 0) as u8).collect();
+        let (p, q) = rational_approximation(&bits);
+        assert_eq!(&p * -3, q);
+        let q = BigInt::from(101);
+        let bits = two_adic_expansion(&BigInt::from(-37), &q, 60);
+        let (p, found) = rational_approximation(&bits);
+        assert_eq!(
+            two_adic_expansion(&p, &found, 200),
+            two_adic_expansion(&BigInt::from(-37), &q, 200)
+        );
+        assert_eq!(found.abs(), q);
+    }
+
+    #[test]
+    fn fcsr_two_adic_complexity() {
+        let config = parse_register("6:1,2,5,6:101100").unwrap();
+        let mut fcsr = Fcsr::new(config.clone());
+        let bits: Vec<u8> = (0..64).map(|_| fcsr.next_bit()).collect();
+        // Сложность не превосходит log2(q) с небольшим запасом на числитель
+        assert!(two_adic_complexity(&bits) < 8.0);
+        let (_, q) = rational_approximation(&bits);
+        assert_eq!(q.abs(), config.connection_integer());
+    }
+
+    #[test]
+    fn primes_test() {
+        assert!(is_prime(&BigInt::from(101)));
+        assert!(!is_prime(&BigInt::from(91)));
+        let n = BigInt::from(1_000_003u64) * BigInt::from(999_983u64) * 4;
+        assert_eq!(
+            prime_factors(&n),
+            vec![
+                BigInt::from(2),
+                BigInt::from(999_983u64),
+                BigInt::from(1_000_003u64)
+            ]
+        );
+    }
+
+    #[test]
+    fn l_sequence_test() {
+        assert!(is_l_sequence(&BigInt::from(101)));
+        assert!(is_l_sequence(&BigInt::from(11)));
+        // Порядок 2 по модулю 7 равен 3
+        assert!(!is_l_sequence(&BigInt::from(7)));
+        for config in Preset::Variant7.registers() {
+            assert!(!is_l_sequence(&config.connection_integer()));
+        }
+    }
+}
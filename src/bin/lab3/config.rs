@@ -0,0 +1,304 @@
+use crate::lfsr::{parse_polynomial, primitive_polynomial};
+use clap::ValueEnum;
+use num_bigint::BigInt;
+use num_traits::One;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Наибольшее число отводов: сумма отводов и переноса регистра хранится в `u8`
+pub const MAX_TAPS: usize = 127;
+
+/// Вид обратной связи регистра
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Feedback {
+    /// Сложение отводов с переносом
+    #[default]
+    Carry,
+    /// Сложение отводов по модулю 2
+    Linear,
+}
+
+/// Параметры одного регистра: длина, отводы обратной связи (нумерация с 1), начальное состояние
+/// и вид обратной связи
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterConfig {
+    pub length: usize,
+    pub taps: Vec<usize>,
+    pub state: Vec<u8>,
+    pub feedback: Feedback,
+}
+
+impl RegisterConfig {
+    /// Регистр с проверкой отводов и начального состояния
+    pub fn new(length: usize, taps: Vec<usize>, state: Vec<u8>) -> Result<Self, String> {
+        if length == 0 {
+            return Err(String::from("register length must be positive"));
+        }
+        if taps.is_empty() {
+            return Err(String::from("register needs at least one tap"));
+        }
+        if let Some(tap) = taps.iter().find(|tap| **tap == 0 || **tap > length) {
+            return Err(format!("tap {tap} is outside of register 1..={length}"));
+        }
+        if let Some((i, tap)) = taps
+            .iter()
+            .enumerate()
+            .find(|(i, tap)| taps[..*i].contains(tap))
+        {
+            return Err(format!("tap {tap} is repeated at position {}", i + 1));
+        }
+        if taps.len() > MAX_TAPS {
+            return Err(format!(
+                "register has {} taps, at most {MAX_TAPS} are supported",
+                taps.len()
+            ));
+        }
+        if state.len() != length {
+            return Err(format!(
+                "initial state has {} bits, register length is {length}",
+                state.len()
+            ));
+        }
+        if state.iter().any(|bit| *bit > 1) {
+            return Err(String::from("initial state must consist of bits"));
+        }
+        Ok(Self {
+            length,
+            taps,
+            state,
+            feedback: Feedback::Carry,
+        })
+    }
+
+    /// Линейный регистр с многочленом связи из показателей `exponents`: член `x^i` дает
+    /// отвод `n - i + 1`, свободный член обязателен
+    pub fn linear(exponents: &[usize], state: Option<Vec<u8>>) -> Result<Self, String> {
+        let length = exponents.iter().copied().max().unwrap_or(0);
+        if !exponents.contains(&0) {
+            return Err(String::from("feedback polynomial needs the constant term"));
+        }
+        let taps = exponents
+            .iter()
+            .filter(|i| **i > 0)
+            .map(|i| length - i + 1)
+            .collect();
+        let state = state.unwrap_or_else(|| vec![1; length]);
+        Self::new(length, taps, state)?.into_linear()
+    }
+
+    /// Регистр с теми же отводами и состоянием, но линейной обратной связью
+    pub fn into_linear(self) -> Result<Self, String> {
+        if self.state.iter().all(|bit| *bit == 0) {
+            return Err(String::from("linear register state must not be all zeros"));
+        }
+        Ok(Self {
+            feedback: Feedback::Linear,
+            ..self
+        })
+    }
+
+    /// Показатели ненулевых членов многочлена связи `C(x) = 1 + sum(x^i)` по убыванию,
+    /// `i = n - T + 1` для отвода `T`, как и у целого связи
+    pub fn connection_exponents(&self) -> Vec<usize> {
+        let mut exponents: Vec<usize> = self
+            .taps
+            .iter()
+            .map(|tap| self.length - tap + 1)
+            .chain([0])
+            .collect();
+        exponents.sort_unstable_by(|a, b| b.cmp(a));
+        exponents
+    }
+
+    /// Целое связи регистра Фибоначчи `q = -1 + sum(2^i)`: отвод `T` регистра длины `n`
+    /// берет бит, выработанный `i = n - T + 1` тактов назад
+    pub fn connection_integer(&self) -> BigInt {
+        self.taps.iter().fold(-BigInt::one(), |q, tap| {
+            q + (BigInt::one() << (self.length - tap + 1))
+        })
+    }
+}
+
+/// Описание в формате `[lfsr:]ДЛИНА:ОТВОДЫ:СОСТОЯНИЕ`, принимаемом [`parse_register`]
+impl fmt::Display for RegisterConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let taps: Vec<String> = self.taps.iter().map(|tap| tap.to_string()).collect();
+        let state: String = self.state.iter().map(|bit| bit.to_string()).collect();
+        if self.feedback == Feedback::Linear {
+            write!(f, "lfsr:")?;
+        }
+        write!(f, "{}:{}:{state}", self.length, taps.join(","))
+    }
+}
+
+/// Разбор строки битов состояния
+fn parse_state(state: &str, s: &str) -> Result<Vec<u8>, String> {
+    state
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '0' => Ok(0),
+            '1' => Ok(1),
+            c => Err(format!("invalid state bit '{c}' in '{s}'")),
+        })
+        .collect()
+}
+
+/// Разбор описания линейного регистра после префикса `lfsr:`: многочлен связи
+/// `x^5+x^2+1[:СОСТОЯНИЕ]`, длина с многочленом из таблицы примитивных или `ДЛИНА:ОТВОДЫ[:СОСТОЯНИЕ]`
+fn parse_lfsr(body: &str, s: &str) -> Result<RegisterConfig, String> {
+    if body.contains('x') {
+        let (polynomial, state) = match body.split_once(':') {
+            Some((polynomial, state)) => (polynomial, Some(parse_state(state, s)?)),
+            None => (body, None),
+        };
+        return RegisterConfig::linear(&parse_polynomial(polynomial)?, state);
+    }
+    if let Ok(length) = body.trim().parse::<usize>() {
+        let exponents = primitive_polynomial(length)
+            .ok_or_else(|| format!("no primitive polynomial of degree {length} in the table"))?;
+        return RegisterConfig::linear(&exponents, None);
+    }
+    parse_register(body)?.into_linear()
+}
+
+/// Разбор описания регистра `ДЛИНА:ОТВОДЫ[:СОСТОЯНИЕ]`, например `96:96,95,45,2` или `5:5,3:10110`.
+/// Отводы через запятую, состояние - строка битов, по умолчанию из одних единиц.
+/// Префикс `lfsr:` задает линейный регистр, например `lfsr:x^5+x^2+1`, `lfsr:32` или `lfsr:5:5,4`
+pub fn parse_register(s: &str) -> Result<RegisterConfig, String> {
+    if let Some(body) = s.trim().strip_prefix("lfsr:") {
+        return parse_lfsr(body, s);
+    }
+    let mut parts = s.trim().split(':');
+    let length = parts
+        .next()
+        .and_then(|length| length.trim().parse::<usize>().ok())
+        .ok_or_else(|| format!("invalid register length in '{s}'"))?;
+    let taps = parts
+        .next()
+        .ok_or_else(|| format!("missing taps in '{s}'"))?
+        .split(',')
+        .map(|tap| tap.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|e| format!("invalid tap in '{s}': {e}"))?;
+    let state = match parts.next() {
+        Some(state) => parse_state(state, s)?,
+        None => vec![1; length],
+    };
+    if parts.next().is_some() {
+        return Err(format!("unexpected fields in '{s}'"));
+    }
+    RegisterConfig::new(length, taps, state)
+}
+
+/// Чтение регистров из файла: по одному описанию регистра на строку, `#` начинает комментарий
+pub fn read_config(path: &Path) -> Result<Vec<RegisterConfig>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let registers = content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(parse_register)
+        .collect::<Result<Vec<_>, _>>()?;
+    if registers.is_empty() {
+        return Err(format!("no registers in {}", path.display()));
+    }
+    Ok(registers)
+}
+
+/// Именованные наборы регистров
+#[derive(ValueEnum, Debug, Clone, Copy, Default)]
+pub enum Preset {
+    /// Три 96-битных регистра по 7 варианту, начальное состояние из единиц
+    #[default]
+    Variant7,
+}
+
+impl Preset {
+    pub fn registers(&self) -> Vec<RegisterConfig> {
+        match self {
+            Preset::Variant7 => ["96:96,95,45,2", "96:96,88,79,2", "96:96,69,17,2"]
+                .into_iter()
+                .map(|s| parse_register(s).expect("valid preset"))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_register, Feedback, Preset, RegisterConfig};
+    use num_bigint::BigInt;
+    use num_traits::One;
+
+    #[test]
+    fn parse_register_test() {
+        assert_eq!(
+            parse_register("5:5,3:10110"),
+            Ok(RegisterConfig {
+                length: 5,
+                taps: vec![5, 3],
+                state: vec![1, 0, 1, 1, 0],
+                feedback: Feedback::Carry,
+            })
+        );
+        assert_eq!(parse_register("3:1").unwrap().state, vec![1, 1, 1]);
+        let config = parse_register("5:5,3:10110").unwrap();
+        assert_eq!(config.to_string(), "5:5,3:10110");
+        assert_eq!(parse_register(&config.to_string()), Ok(config));
+    }
+
+    #[test]
+    fn invalid_registers() {
+        assert!(parse_register("5:6").is_err());
+        assert!(parse_register("5:0").is_err());
+        assert!(parse_register("0:1").is_err());
+        assert!(parse_register("5:5,3:101").is_err());
+        assert!(parse_register("5:5:10120").is_err());
+        assert!(parse_register("5").is_err());
+        assert!(parse_register("5:5,3,5").is_err());
+        let taps: Vec<String> = (1..=200).map(|tap| tap.to_string()).collect();
+        assert!(parse_register(&format!("200:{}", taps.join(","))).is_err());
+        assert!(parse_register(&format!("127:{}", taps[..127].join(","))).is_ok());
+    }
+
+    #[test]
+    fn connection_integer_test() {
+        assert_eq!(
+            parse_register("6:1,2,5,6").unwrap().connection_integer(),
+            BigInt::from(101)
+        );
+        let q = (BigInt::one() << 95) + (BigInt::one() << 52) + BigInt::from(5);
+        assert_eq!(Preset::Variant7.registers()[0].connection_integer(), q);
+    }
+
+    #[test]
+    fn linear_registers() {
+        let config = parse_register("lfsr:x^5+x^2+1:10110").unwrap();
+        assert_eq!(config.feedback, Feedback::Linear);
+        assert_eq!((config.length, config.taps.clone()), (5, vec![1, 4]));
+        assert_eq!(config.connection_exponents(), vec![5, 2, 0]);
+        assert_eq!(config.to_string(), "lfsr:5:1,4:10110");
+        assert_eq!(
+            parse_register("lfsr:5:5,3").unwrap().connection_exponents(),
+            vec![3, 1, 0]
+        );
+        assert_eq!(parse_register(&config.to_string()), Ok(config));
+        assert_eq!(
+            parse_register("lfsr:32").unwrap().connection_exponents(),
+            vec![32, 22, 2, 1, 0]
+        );
+        assert!(parse_register("lfsr:x^5+x^2").is_err());
+        assert!(parse_register("lfsr:x^3+x+1:000").is_err());
+        assert!(parse_register("lfsr:65").is_err());
+    }
+
+    #[test]
+    fn variant7_preset() {
+        let registers = Preset::Variant7.registers();
+        assert_eq!(registers.len(), 3);
+        assert_eq!(registers[0].taps, vec![96, 95, 45, 2]);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::config::RegisterConfig;
+use num_bigint::{BigInt, Sign};
+use num_traits::One;
+
+/// Регистр сдвига с обратной связью по переносу в схеме Галуа. Основной регистр и регистр
+/// переносов упакованы в 64-битные слова, такт выполняется словными операциями.
+/// Для целого связи `q` используется `d = (q + 1) / 2`: переносы стоят в разрядах единиц `d`,
+/// выход - 2-адическое разложение дроби со знаменателем `q`, как и у схемы Фибоначчи
+#[derive(Clone, Debug)]
+pub struct GaloisFcsr {
+    main: Vec<u64>,
+    carry: Vec<u64>,
+    d: Vec<u64>,
+    length: usize,
+}
+
+impl GaloisFcsr {
+    /// Регистр по нечетному положительному целому связи `q` и начальному состоянию
+    /// основного регистра, младший бит первый. Лишние биты состояния отбрасываются
+    pub fn new(q: &BigInt, state: &[u8]) -> Self {
+        let d: BigInt = (q + BigInt::one()) >> 1;
+        assert_eq!(
+            d.sign(),
+            Sign::Plus,
+            "connection integer must be at least 3"
+        );
+        let length = d.bits() as usize;
+        let mut d = d.to_u64_digits().1;
+        d.resize(length.div_ceil(64), 0);
+        let mut register = Self {
+            main: vec![0; d.len()],
+            carry: vec![0; d.len()],
+            d,
+            length,
+        };
+        register.load(state, &[]);
+        register
+    }
+
+    /// Регистр с тем же целым связи, что и регистр Фибоначчи из конфигурации
+    pub fn from_config(config: &RegisterConfig) -> Self {
+        Self::new(&config.connection_integer(), &config.state)
+    }
+
+    /// Число разрядов основного регистра
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Число ячеек регистра переносов: по одной на каждый единичный разряд `d`
+    pub fn carry_cells(&self) -> usize {
+        self.d.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Загрузка основного регистра и переносов, `carry[k]` попадает в `k`-й единичный
+    /// разряд `d`. Недостающие переносы обнуляются
+    pub fn load(&mut self, state: &[u8], carry: &[u8]) {
+        self.main.fill(0);
+        self.carry.fill(0);
+        for (i, bit) in state.iter().take(self.length).enumerate() {
+            self.main[i / 64] |= (*bit as u64 & 1) << (i % 64);
+        }
+        let cells = (0..self.length).filter(|i| self.d[i / 64] >> (i % 64) & 1 == 1);
+        for (i, bit) in cells.zip(carry) {
+            self.carry[i / 64] |= (*bit as u64 & 1) << (i % 64);
+        }
+    }
+
+    /// Такт: выходом служит младший разряд, основной регистр сдвигается вправо и в разрядах
+    /// `d` складывается с переносом и выходным битом
+    #[inline]
+    fn clock(main: &mut [u64], carry: &mut [u64], d: &[u64]) -> u64 {
+        let output = main[0] & 1;
+        let feedback = output.wrapping_neg();
+        let words = main.len();
+        for w in 0..words {
+            let next = if w + 1 < words { main[w + 1] << 63 } else { 0 };
+            let shifted = (main[w] >> 1) | next;
+            let d = d[w] & feedback;
+            let c = carry[w];
+            main[w] = shifted ^ c ^ d;
+            carry[w] = (shifted & c) | (shifted & d) | (c & d);
+        }
+        output
+    }
+
+    /// Один такт, в шифре биты выдаются из слов через [`BufferedGalois`]
+    #[cfg(test)]
+    pub fn next_bit(&mut self) -> u8 {
+        Self::clock(&mut self.main, &mut self.carry, &self.d) as u8
+    }
+
+    /// 64 бита гаммы, первый бит в младшем разряде. Регистры до 128 разрядов
+    /// тактуются целиком в `u128` без прохода по словам
+    pub fn next_word(&mut self) -> u64 {
+        if self.main.len() > 2 {
+            let (main, carry, d) = (&mut self.main[..], &mut self.carry[..], &self.d[..]);
+            return (0..64).fold(0, |word, i| word | Self::clock(main, carry, d) << i);
+        }
+        let pack = |words: &[u64]| {
+            words
+                .iter()
+                .rev()
+                .fold(0u128, |value, word| (value << 64) | *word as u128)
+        };
+        let (mut main, mut carry, d) = (pack(&self.main), pack(&self.carry), pack(&self.d));
+        let mut word = 0;
+        for i in 0..64 {
+            let output = main & 1;
+            let shifted = main >> 1;
+            let d = d & output.wrapping_neg();
+            word |= (output as u64) << i;
+            main = shifted ^ carry ^ d;
+            carry = (shifted & carry) | (shifted & d) | (carry & d);
+        }
+        for (w, (m, c)) in self.main.iter_mut().zip(self.carry.iter_mut()).enumerate() {
+            *m = (main >> (64 * w)) as u64;
+            *c = (carry >> (64 * w)) as u64;
+        }
+        word
+    }
+}
+
+/// Регистр Галуа в генераторе гаммы: выход вырабатывается словами по 64 бита,
+/// а биты выдаются из последнего слова
+#[derive(Clone, Debug)]
+pub struct BufferedGalois {
+    fcsr: GaloisFcsr,
+    word: u64,
+    remaining: u32,
+}
+
+impl BufferedGalois {
+    pub fn new(fcsr: GaloisFcsr) -> Self {
+        Self {
+            fcsr,
+            word: 0,
+            remaining: 0,
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.fcsr.length()
+    }
+
+    pub fn carry_cells(&self) -> usize {
+        self.fcsr.carry_cells()
+    }
+
+    /// Загрузка основного регистра и переносов, невыданные биты прежнего слова отбрасываются
+    pub fn load(&mut self, state: &[u8], carry: &[u8]) {
+        self.fcsr.load(state, carry);
+        self.remaining = 0;
+    }
+
+    #[inline]
+    pub fn next_bit(&mut self) -> u8 {
+        if self.remaining == 0 {
+            self.word = self.fcsr.next_word();
+            self.remaining = 64;
+        }
+        let bit = (self.word & 1) as u8;
+        self.word >>= 1;
+        self.remaining -= 1;
+        bit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferedGalois, GaloisFcsr};
+    use crate::config::{parse_register, Preset};
+    use crate::Fcsr;
+    use num_bigint::BigInt;
+    use num_traits::{One, Signed, Zero};
+
+    /// Последовательность является 2-адическим разложением `a / q` с небольшим `a`:
+    /// `q * S` по модулю `2^N` близко к нулю с одной из сторон
+    fn is_two_adic(bits: &[u8], q: &BigInt) -> bool {
+        let modulus = BigInt::one() << bits.len();
+        let s = bits
+            .iter()
+            .rev()
+            .fold(BigInt::zero(), |s, bit| (s << 1) + BigInt::from(*bit));
+        let a = (q * s) % &modulus;
+        let a = if a > &modulus >> 1 { a - modulus } else { a };
+        a.abs() < (q << 4)
+    }
+
+    fn fibonacci_bits(config: &str, len: usize) -> Vec<u8> {
+        let mut fcsr = Fcsr::new(parse_register(config).unwrap());
+        (0..len).map(|_| fcsr.next_bit()).collect()
+    }
+
+    fn galois_bits(config: &str, len: usize) -> Vec<u8> {
+        let mut fcsr = GaloisFcsr::from_config(&parse_register(config).unwrap());
+        (0..len).map(|_| fcsr.next_bit()).collect()
+    }
+
+    #[test]
+    fn same_two_adic_denominator() {
+        for config in ["6:1,2,5,6:101100", "13:13,7,4,1:1011001110001"] {
+            let q = parse_register(config).unwrap().connection_integer();
+            assert!(is_two_adic(&fibonacci_bits(config, 400), &q));
+            assert!(is_two_adic(&galois_bits(config, 400), &q));
+        }
+        let random: Vec<u8> = (0..400).map(|i| ((i * 7 + i / 3) % 5 % 2) as u8).collect();
+        assert!(!is_two_adic(&random, &BigInt::from(101)));
+    }
+
+    #[test]
+    fn variant7_two_adic() {
+        for config in Preset::Variant7.registers() {
+            let q = config.connection_integer();
+            let mut fibonacci = Fcsr::new(config.clone());
+            let mut galois = GaloisFcsr::from_config(&config);
+            let fibonacci: Vec<u8> = (0..1024).map(|_| fibonacci.next_bit()).collect();
+            let galois: Vec<u8> = (0..1024).map(|_| galois.next_bit()).collect();
+            assert!(is_two_adic(&fibonacci, &q));
+            assert!(is_two_adic(&galois, &q));
+        }
+    }
+
+    #[test]
+    fn l_sequence_shift() {
+        // 2 - первообразный корень по модулю 101: периодические части выходов обеих схем
+        // совпадают с точностью до сдвига
+        let config = "6:1,2,5,6:101100";
+        let fibonacci = fibonacci_bits(config, 300)[100..200].to_vec();
+        let galois = galois_bits(config, 300)[100..200].to_vec();
+        assert!(
+            (0..100).any(|shift| { (0..100).all(|i| fibonacci[i] == galois[(i + shift) % 100]) })
+        );
+    }
+
+    #[test]
+    fn word_output() {
+        for config in ["6:1,2,5,6:101100", "150:150,100,3,1"] {
+            let config = parse_register(config).unwrap();
+            let mut by_bit = GaloisFcsr::from_config(&config);
+            let mut by_word = GaloisFcsr::from_config(&config);
+            for _ in 0..4 {
+                let word = by_word.next_word();
+                for i in 0..64 {
+                    assert_eq!(by_bit.next_bit() as u64, (word >> i) & 1);
+                }
+            }
+        }
+        let config = Preset::Variant7.registers().remove(1);
+        let mut by_bit = GaloisFcsr::from_config(&config);
+        let mut by_word = GaloisFcsr::from_config(&config);
+        for _ in 0..4 {
+            let word = by_word.next_word();
+            for i in 0..64 {
+                assert_eq!(by_bit.next_bit() as u64, (word >> i) & 1);
+            }
+        }
+        assert_eq!(by_word.length(), 95);
+    }
+
+    #[test]
+    fn buffered_bits() {
+        let config = Preset::Variant7.registers().remove(0);
+        let mut by_bit = GaloisFcsr::from_config(&config);
+        let mut buffered = BufferedGalois::new(GaloisFcsr::from_config(&config));
+        for _ in 0..200 {
+            assert_eq!(buffered.next_bit(), by_bit.next_bit());
+        }
+        let state = vec![1, 0, 1, 1];
+        by_bit.load(&state, &[]);
+        buffered.load(&state, &[]);
+        for _ in 0..200 {
+            assert_eq!(buffered.next_bit(), by_bit.next_bit());
+        }
+        let carry = vec![1; by_bit.carry_cells()];
+        by_bit.load(&state, &carry);
+        buffered.load(&state, &carry);
+        for _ in 0..200 {
+            assert_eq!(buffered.next_bit(), by_bit.next_bit());
+        }
+    }
+
+    #[test]
+    fn loaded_carries() {
+        let config = parse_register("6:1,2,5,6:101100").unwrap();
+        let mut fcsr = GaloisFcsr::from_config(&config);
+        // d = (101 + 1) / 2 = 51 = 110011b
+        assert_eq!(fcsr.carry_cells(), 4);
+        let mut outputs = Vec::new();
+        for carry in [[0, 0, 0, 0], [1, 0, 0, 0], [0, 0, 0, 1]] {
+            fcsr.load(&config.state, &carry);
+            let bits: Vec<u8> = (0..100).map(|_| fcsr.next_bit()).collect();
+            assert!(is_two_adic(&bits, &config.connection_integer()));
+            outputs.push(bits);
+        }
+        assert_ne!(outputs[0], outputs[1]);
+        assert_ne!(outputs[0], outputs[2]);
+        assert_ne!(outputs[1], outputs[2]);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{Cipher, Direction, SymbolBits};
+use console::{Key, Term};
+use std::io::{self, IsTerminal};
+
+/// Что вводится с клавиатуры: открытый текст или шифротекст
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Encrypt,
+    Decrypt,
+}
+
+/// Байты из шестнадцатеричных цифр ввода, прочие символы и неполный последний байт пропускаются
+fn hex_bytes(input: &str) -> Vec<u8> {
+    let digits: Vec<u8> = input
+        .chars()
+        .filter_map(|c| c.to_digit(16))
+        .map(|d| d as u8)
+        .collect();
+    digits.chunks_exact(2).map(|d| (d[0] << 4) | d[1]).collect()
+}
+
+/// Результат обработки всего ввода заново с начала гаммы. В байтовом режиме шифротекст
+/// показывается и вводится в hex, `None` - старый текстовый режим
+fn process(cipher: &Cipher, bits: Option<SymbolBits>, view: View, input: &str) -> String {
+    let mut cipher = cipher.clone();
+    match (bits, view) {
+        (None, _) => cipher.process_str(input),
+        (Some(bits), View::Encrypt) => cipher
+            .process_bytes(input.as_bytes(), bits, Direction::Encrypt)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+        (Some(bits), View::Decrypt) => String::from_utf8_lossy(&cipher.process_bytes(
+            &hex_bytes(input),
+            bits,
+            Direction::Decrypt,
+        ))
+        .into_owned(),
+    }
+}
+
+/// Интерактивный режим: ввод обрабатывается по мере набора. Tab переключает шифрование
+/// и дешифрование, Backspace удаляет последний символ, Esc завершает работу
+pub fn run(cipher: &Cipher, bits: Option<SymbolBits>) -> io::Result<()> {
+    let term = Term::stdout();
+    if !term.is_term() || !io::stdin().is_terminal() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotConnected,
+            "not a terminal",
+        ));
+    }
+    let mut view = View::Encrypt;
+    let mut input = String::new();
+    loop {
+        term.clear_screen()?;
+        let (input_label, output_label) = match view {
+            View::Encrypt => ("plaintext", "ciphertext"),
+            View::Decrypt => ("ciphertext", "plaintext"),
+        };
+        term.write_line(&format!(
+            "[{view:?}] Tab: switch view, Backspace: erase, Esc: quit"
+        ))?;
+        term.write_line(&format!("{input_label}: \"{input}\""))?;
+        term.write_line(&format!(
+            "{output_label}: \"{}\"",
+            process(cipher, bits, view, &input)
+        ))?;
+        match term.read_key()? {
+            Key::Escape => return Ok(()),
+            Key::Tab => {
+                view = match view {
+                    View::Encrypt => View::Decrypt,
+                    View::Decrypt => View::Encrypt,
+                };
+                input.clear();
+            }
+            Key::Backspace => {
+                input.pop();
+            }
+            Key::Enter => input.push('\n'),
+            Key::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex_bytes, process, View};
+    use crate::{Cipher, SymbolBits};
+
+    #[test]
+    fn hex_bytes_test() {
+        assert_eq!(hex_bytes("0aFf 1"), vec![0x0a, 0xff]);
+    }
+
+    #[test]
+    fn decrypt_view() {
+        let cipher = Cipher::new();
+        let bits = Some(SymbolBits::Sixteen);
+        let encrypted = process(&cipher, bits, View::Encrypt, "просмотр");
+        assert_eq!(encrypted.len(), "просмотр".len() * 2);
+        assert_eq!(
+            process(&cipher, bits, View::Decrypt, &encrypted),
+            "просмотр"
+        );
+        let encrypted = process(&cipher, None, View::Encrypt, "текст");
+        assert_eq!(process(&cipher, None, View::Decrypt, &encrypted), "текст");
+    }
+}
@@ -0,0 +1,120 @@
+use sha2::{Digest, Sha256};
+
+/// Разбор ключа или синхропосылки в шестнадцатеричной записи
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    let s = s.trim().trim_start_matches("0x");
+    if s.is_empty() || !s.len().is_multiple_of(2) {
+        return Err(format!(
+            "'{s}' must have an even positive number of hex digits"
+        ));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex digits in '{s}'"))
+        })
+        .collect()
+}
+
+/// Ключ из пароля: SHA-256 от байтов пароля в UTF-8
+pub fn password_key(password: &str) -> Vec<u8> {
+    Sha256::digest(password.as_bytes()).to_vec()
+}
+
+/// Ключ, синхропосылка и число холостых тактов после их загрузки в регистры
+#[derive(Debug, Clone)]
+pub struct KeySetup {
+    pub key: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub warmup: usize,
+}
+
+impl KeySetup {
+    /// Поток байтов для регистра с номером `index`: SHA-256 от ключа, синхропосылки,
+    /// номера регистра и счетчика блоков. Длины ключа и синхропосылки входят в хэш,
+    /// чтобы разные пары не давали одинаковую строку
+    fn register_bytes(&self, index: usize, len: usize) -> Vec<u8> {
+        let mut ans = Vec::with_capacity(len);
+        let mut counter: u64 = 0;
+        while ans.len() < len {
+            let mut hasher = Sha256::new();
+            hasher.update((self.key.len() as u64).to_be_bytes());
+            hasher.update(&self.key);
+            hasher.update((self.nonce.len() as u64).to_be_bytes());
+            hasher.update(&self.nonce);
+            hasher.update((index as u64).to_be_bytes());
+            hasher.update(counter.to_be_bytes());
+            ans.extend_from_slice(&hasher.finalize());
+            counter += 1;
+        }
+        ans.truncate(len);
+        ans
+    }
+
+    /// Начальное состояние регистра длины `length` и перенос не больше `max_carry`.
+    /// Нулевое состояние с нулевым переносом вырождено, поэтому младший бит тогда взводится
+    pub fn register_state(&self, index: usize, length: usize, max_carry: usize) -> (Vec<u8>, u8) {
+        let bytes = self.register_bytes(index, length.div_ceil(8) + 1);
+        let mut state: Vec<u8> = (0..length).map(|i| (bytes[i / 8] >> (i % 8)) & 1).collect();
+        let carry = (bytes[bytes.len() - 1] as usize % (max_carry + 1)) as u8;
+        if carry == 0 && state.iter().all(|bit| *bit == 0) {
+            state[0] = 1;
+        }
+        (state, carry)
+    }
+
+    /// Начальное состояние регистра Галуа: основной регистр длины `length` из тех же битов,
+    /// что и в [`Self::register_state`], и `carries` переносов из следующих байтов потока.
+    /// Нулевые состояние и переносы вырождены, поэтому младший бит тогда взводится
+    pub fn galois_state(&self, index: usize, length: usize, carries: usize) -> (Vec<u8>, Vec<u8>) {
+        let offset = 8 * (length.div_ceil(8) + 1);
+        let bytes = self.register_bytes(index, (offset + carries).div_ceil(8));
+        let bit = |i: usize| (bytes[i / 8] >> (i % 8)) & 1;
+        let mut state: Vec<u8> = (0..length).map(bit).collect();
+        let carry: Vec<u8> = (offset..offset + carries).map(bit).collect();
+        if state.iter().chain(&carry).all(|bit| *bit == 0) {
+            state[0] = 1;
+        }
+        (state, carry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_hex, password_key, KeySetup};
+
+    #[test]
+    fn parse_hex_test() {
+        assert_eq!(parse_hex("00ff1A"), Ok(vec![0x00, 0xff, 0x1a]));
+        assert_eq!(parse_hex("0x0102"), Ok(vec![1, 2]));
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("zz").is_err());
+        assert!(parse_hex("").is_err());
+    }
+
+    #[test]
+    fn password_key_test() {
+        assert_eq!(password_key("secret").len(), 32);
+        assert_ne!(password_key("secret"), password_key("Secret"));
+    }
+
+    #[test]
+    fn register_state_test() {
+        let setup = KeySetup {
+            key: vec![1, 2, 3],
+            nonce: vec![0],
+            warmup: 0,
+        };
+        let (state, carry) = setup.register_state(0, 96, 3);
+        assert_eq!(state.len(), 96);
+        assert!(carry <= 3);
+        assert_eq!(setup.register_state(0, 96, 3), (state.clone(), carry));
+        assert_ne!(setup.register_state(1, 96, 3).0, state);
+        let (galois, carry) = setup.galois_state(0, 96, 20);
+        assert_eq!(galois, state);
+        assert_eq!(carry.len(), 20);
+        assert_ne!(setup.galois_state(1, 96, 20).1, carry);
+    }
+}
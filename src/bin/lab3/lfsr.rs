@@ -0,0 +1,371 @@
+use crate::complexity::prime_factors;
+use crate::config::RegisterConfig;
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use std::fmt;
+
+/// Примитивные многочлены `x^n + x^k + ... + 1` для распространенных длин регистров:
+/// степень и показатели средних членов. Для каждой степени выбран трехчлен с наименьшим `k`,
+/// а если примитивных трехчленов нет - пятичлен
+const PRIMITIVE: &[(usize, &[usize])] = &[
+    (2, &[1]),
+    (3, &[1]),
+    (4, &[1]),
+    (5, &[2]),
+    (6, &[1]),
+    (7, &[1]),
+    (8, &[7, 2, 1]),
+    (9, &[4]),
+    (10, &[3]),
+    (11, &[2]),
+    (12, &[8, 2, 1]),
+    (13, &[5, 2, 1]),
+    (14, &[12, 2, 1]),
+    (15, &[1]),
+    (16, &[12, 3, 1]),
+    (17, &[3]),
+    (18, &[7]),
+    (19, &[5, 2, 1]),
+    (20, &[3]),
+    (21, &[2]),
+    (22, &[1]),
+    (23, &[5]),
+    (24, &[7, 2, 1]),
+    (25, &[3]),
+    (26, &[6, 2, 1]),
+    (27, &[5, 2, 1]),
+    (28, &[3]),
+    (29, &[2]),
+    (30, &[23, 2, 1]),
+    (31, &[3]),
+    (32, &[22, 2, 1]),
+    (33, &[13]),
+    (34, &[27, 2, 1]),
+    (35, &[2]),
+    (36, &[11]),
+    (37, &[9, 2, 1]),
+    (38, &[13, 3, 1]),
+    (39, &[4]),
+    (40, &[35, 2, 1]),
+    (41, &[3]),
+    (42, &[29, 2, 1]),
+    (43, &[12, 2, 1]),
+    (44, &[38, 3, 1]),
+    (45, &[4, 3, 1]),
+    (46, &[9, 3, 1]),
+    (47, &[5]),
+    (48, &[28, 3, 1]),
+    (49, &[9]),
+    (50, &[16, 2, 1]),
+    (51, &[28, 2, 1]),
+    (52, &[3]),
+    (53, &[6, 2, 1]),
+    (54, &[17, 2, 1]),
+    (55, &[24]),
+    (56, &[42, 2, 1]),
+    (57, &[7]),
+    (58, &[19]),
+    (59, &[24, 2, 1]),
+    (60, &[1]),
+    (61, &[5, 2, 1]),
+    (62, &[28, 3, 1]),
+    (63, &[1]),
+    (64, &[11, 2, 1]),
+    (96, &[19, 2, 1]),
+    (127, &[1]),
+];
+
+/// Показатели примитивного многочлена степени `degree` из таблицы, по убыванию
+pub fn primitive_polynomial(degree: usize) -> Option<Vec<usize>> {
+    let (_, middle) = PRIMITIVE.iter().find(|(n, _)| *n == degree)?;
+    Some([&[degree], *middle, &[0]].concat())
+}
+
+/// Степени, для которых в таблице есть примитивный многочлен
+pub fn primitive_degrees() -> impl Iterator<Item = usize> {
+    PRIMITIVE.iter().map(|(n, _)| *n)
+}
+
+/// Разбор многочлена вида `x^5+x^2+1`: показатели ненулевых членов по убыванию
+pub fn parse_polynomial(s: &str) -> Result<Vec<usize>, String> {
+    let mut exponents = s
+        .split('+')
+        .map(|term| match term.trim() {
+            "1" => Ok(0),
+            "x" => Ok(1),
+            term => term
+                .strip_prefix("x^")
+                .and_then(|exponent| exponent.trim().parse::<usize>().ok())
+                .ok_or_else(|| format!("invalid term '{term}' in '{s}'")),
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    exponents.sort_unstable_by(|a, b| b.cmp(a));
+    if exponents.windows(2).any(|w| w[0] == w[1]) {
+        return Err(format!("repeated term in '{s}'"));
+    }
+    Ok(exponents)
+}
+
+/// Многочлен над GF(2) степени не выше 127, бит `i` - коэффициент при `x^i`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Polynomial(pub u128);
+
+impl Polynomial {
+    pub const MAX_DEGREE: usize = 127;
+
+    /// Многочлен по показателям ненулевых членов, `None` для степени больше 127
+    pub fn from_exponents(exponents: &[usize]) -> Option<Self> {
+        exponents.iter().try_fold(Self(0), |p, e| {
+            (*e <= Self::MAX_DEGREE).then(|| Self(p.0 ^ (1 << e)))
+        })
+    }
+
+    pub fn degree(&self) -> usize {
+        (127 - self.0.leading_zeros().min(127)) as usize
+    }
+
+    /// Произведение `a * b` по модулю многочлена степени `n`, `a` и `b` уже приведены
+    fn mul_mod(&self, mut a: u128, mut b: u128, n: usize) -> u128 {
+        let mut product = 0;
+        while b != 0 {
+            if b & 1 == 1 {
+                product ^= a;
+            }
+            b >>= 1;
+            a <<= 1;
+            if (a >> n) & 1 == 1 {
+                a ^= self.0;
+            }
+        }
+        product
+    }
+
+    /// `x^e` по модулю многочлена
+    fn pow_x(&self, mut e: u128) -> u128 {
+        let n = self.degree();
+        let mut base = if n > 1 { 2 } else { 1 };
+        let mut result = 1;
+        while e != 0 {
+            if e & 1 == 1 {
+                result = self.mul_mod(result, base, n);
+            }
+            base = self.mul_mod(base, base, n);
+            e >>= 1;
+        }
+        result
+    }
+
+    /// Многочлен степени `n` со свободным членом примитивен, если порядок `x` по его модулю
+    /// равен `2^n - 1`. Для приводимого многочлена обратимых вычетов меньше `2^n - 1`,
+    /// поэтому отдельная проверка неприводимости не нужна
+    pub fn is_primitive(&self) -> bool {
+        let n = self.degree();
+        if n == 0 || self.0 & 1 == 0 {
+            return false;
+        }
+        let order = u128::MAX >> (128 - n);
+        if self.pow_x(order) != 1 {
+            return false;
+        }
+        prime_factors(&BigInt::from(order))
+            .iter()
+            .filter_map(|r| r.to_u128())
+            .all(|r| self.pow_x(order / r) != 1)
+    }
+}
+
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = (0..=self.degree())
+            .rev()
+            .filter(|i| (self.0 >> i) & 1 == 1)
+            .map(|i| match i {
+                0 => String::from("1"),
+                1 => String::from("x"),
+                i => format!("x^{i}"),
+            })
+            .collect();
+        if terms.is_empty() {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", terms.join(" + "))
+        }
+    }
+}
+
+/// Линейный регистр сдвига в схеме Фибоначчи: новый бит - сумма по модулю 2 отводов.
+/// Отводы и состояние те же, что у регистра с переносом, без переноса
+#[derive(Clone, Debug)]
+pub struct Lfsr {
+    state: Vec<u8>,
+    taps: Vec<usize>,
+    length: usize,
+}
+
+impl Lfsr {
+    pub fn new(config: &RegisterConfig) -> Self {
+        Self {
+            state: config.state.clone(),
+            taps: config.taps.iter().map(|tap| tap - 1).collect(),
+            length: config.length,
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn next_bit(&mut self) -> u8 {
+        let new_bit = self.taps.iter().fold(0, |bit, tap| bit ^ self.state[*tap]);
+        self.state.rotate_left(1);
+        self.state[self.length - 1] = new_bit;
+        new_bit
+    }
+
+    pub fn load(&mut self, state: Vec<u8>) {
+        self.state = state;
+    }
+}
+
+/// Линейный регистр сдвига в схеме Галуа с состоянием в 64-битных словах. При выходе единицы
+/// сдвинутое вправо состояние складывается с маской `(C(x) - 1) / x`, где `C(x)` - многочлен
+/// связи регистра Фибоначчи с теми же отводами, поэтому обе схемы дают последовательности
+/// с одним многочленом связи
+#[derive(Clone, Debug)]
+pub struct GaloisLfsr {
+    state: Vec<u64>,
+    mask: Vec<u64>,
+    length: usize,
+}
+
+impl GaloisLfsr {
+    pub fn from_config(config: &RegisterConfig) -> Self {
+        let length = config.length;
+        let mut mask = vec![0; length.div_ceil(64)];
+        for exponent in config.connection_exponents() {
+            if exponent > 0 {
+                mask[(exponent - 1) / 64] |= 1 << ((exponent - 1) % 64);
+            }
+        }
+        let mut register = Self {
+            state: vec![0; mask.len()],
+            mask,
+            length,
+        };
+        register.load(&config.state);
+        register
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Загрузка состояния, младший бит первый
+    pub fn load(&mut self, state: &[u8]) {
+        self.state.fill(0);
+        for (i, bit) in state.iter().take(self.length).enumerate() {
+            self.state[i / 64] |= (*bit as u64 & 1) << (i % 64);
+        }
+    }
+
+    pub fn next_bit(&mut self) -> u8 {
+        let output = self.state[0] & 1;
+        let feedback = output.wrapping_neg();
+        let words = self.state.len();
+        for w in 0..words {
+            let next = if w + 1 < words {
+                self.state[w + 1] << 63
+            } else {
+                0
+            };
+            self.state[w] = ((self.state[w] >> 1) | next) ^ (self.mask[w] & feedback);
+        }
+        output as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::complexity::linear_complexity_profile;
+    use crate::config::parse_register;
+
+    fn period(mut next_bit: impl FnMut() -> u8, length: usize, limit: usize) -> Option<usize> {
+        let bits: Vec<u8> = (0..limit + length).map(|_| next_bit()).collect();
+        (1..=limit).find(|p| (0..length).all(|i| bits[i] == bits[i + p]))
+    }
+
+    #[test]
+    fn table_is_primitive() {
+        for degree in primitive_degrees() {
+            let exponents = primitive_polynomial(degree).unwrap();
+            let polynomial = Polynomial::from_exponents(&exponents).unwrap();
+            assert_eq!(polynomial.degree(), degree);
+            assert!(polynomial.is_primitive(), "{polynomial}");
+        }
+        assert!(primitive_polynomial(65).is_none());
+    }
+
+    #[test]
+    fn primitivity() {
+        let check = |s: &str| {
+            Polynomial::from_exponents(&parse_polynomial(s).unwrap())
+                .unwrap()
+                .is_primitive()
+        };
+        assert!(check("x^4+x+1"));
+        assert!(check("x^4+x^3+1"));
+        // Приводимый: (x^2 + x + 1)^2
+        assert!(!check("x^4+x^2+1"));
+        // Неприводимый, но порядок x равен 5
+        assert!(!check("x^4+x^3+x^2+x+1"));
+        assert!(!check("x^5+x^2"));
+        assert!(check("x+1"));
+    }
+
+    #[test]
+    fn polynomial_text() {
+        assert_eq!(parse_polynomial("x^5 + x^2 + 1"), Ok(vec![5, 2, 0]));
+        assert_eq!(parse_polynomial("1+x+x^3"), Ok(vec![3, 1, 0]));
+        assert!(parse_polynomial("x^2+y").is_err());
+        assert!(parse_polynomial("x+x").is_err());
+        let polynomial = Polynomial::from_exponents(&[5, 2, 0]).unwrap();
+        assert_eq!(polynomial.to_string(), "x^5 + x^2 + 1");
+        assert!(Polynomial::from_exponents(&[128, 0]).is_none());
+    }
+
+    #[test]
+    fn maximal_period() {
+        for s in ["lfsr:x^5+x^2+1:10000", "lfsr:x^7+x+1", "lfsr:10"] {
+            let config = parse_register(s).unwrap();
+            let n = config.length;
+            let mut fibonacci = Lfsr::new(&config);
+            let mut galois = GaloisLfsr::from_config(&config);
+            assert_eq!(
+                period(|| fibonacci.next_bit(), n, 1 << n),
+                Some((1 << n) - 1)
+            );
+            assert_eq!(period(|| galois.next_bit(), n, 1 << n), Some((1 << n) - 1));
+        }
+        // x^4 + x^2 + 1 не примитивен, период меньше 15
+        let config = parse_register("lfsr:x^4+x^2+1:1000").unwrap();
+        let mut lfsr = Lfsr::new(&config);
+        assert_eq!(period(|| lfsr.next_bit(), 4, 16), Some(6));
+    }
+
+    #[test]
+    fn same_connection_polynomial() {
+        let config = parse_register("lfsr:x^13+x^5+x^2+x+1:1011001110001").unwrap();
+        let mut fibonacci = Lfsr::new(&config);
+        let mut galois = GaloisLfsr::from_config(&config);
+        let fibonacci: Vec<u8> = (0..200).map(|_| fibonacci.next_bit()).collect();
+        let galois: Vec<u8> = (0..200).map(|_| galois.next_bit()).collect();
+        assert_eq!(linear_complexity_profile(&fibonacci)[199], 13);
+        assert_eq!(linear_complexity_profile(&galois)[199], 13);
+        // Обе последовательности удовлетворяют рекурренте a[k] = a[k-13] + a[k-5] + a[k-2] + a[k-1]
+        for bits in [fibonacci, galois] {
+            assert!((13..200)
+                .all(|k| bits[k] == bits[k - 13] ^ bits[k - 5] ^ bits[k - 2] ^ bits[k - 1]));
+        }
+    }
+}
@@ -0,0 +1,1125 @@
+mod attack;
+mod complexity;
+mod config;
+mod galois;
+mod interactive;
+mod key;
+mod lfsr;
+mod randtest;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_verbosity_flag::{Verbosity, WarnLevel};
+use config::{parse_register, read_config, Feedback, Preset, RegisterConfig};
+use galois::{BufferedGalois, GaloisFcsr};
+use key::{parse_hex, password_key, KeySetup};
+use lfsr::{GaloisLfsr, Lfsr, Polynomial};
+use log::{debug, error, warn};
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+
+// Регистр сдвига с обратной связью по переносу
+#[derive(Clone)]
+struct Fcsr {
+    state: Vec<u8>,
+    taps: Vec<usize>,
+    carry: u8,
+    length: usize,
+}
+
+impl Fcsr {
+    /// Регистр из проверенной конфигурации, отводы переводятся в индексы состояния
+    fn new(config: RegisterConfig) -> Self {
+        Fcsr {
+            state: config.state,
+            taps: config.taps.iter().map(|tap| tap - 1).collect(),
+            carry: 0,
+            length: config.length,
+        }
+    }
+
+    fn next_bit(&mut self) -> u8 {
+        let mut feedback = self.carry;
+
+        for &tap in &self.taps {
+            feedback += self.state[tap];
+        }
+
+        let new_bit = feedback % 2;
+        self.carry = feedback / 2;
+
+        self.state.rotate_left(1);
+        self.state[self.length - 1] = new_bit;
+        new_bit
+    }
+
+    /// Загрузка состояния и переноса, полученных из ключа и синхропосылки
+    fn load(&mut self, state: Vec<u8>, carry: u8) {
+        self.state = state;
+        self.carry = carry;
+    }
+}
+
+/// Схема регистров сдвига
+#[derive(ValueEnum, Debug, Clone, Copy, Default)]
+enum Architecture {
+    /// Обратная связь собирается с отводов в новый бит, состояние по биту на байт
+    #[default]
+    Fibonacci,
+    /// Переносы распределены по разрядам, состояние упаковано в слова. Целое связи то же,
+    /// но цепь другая, а длина основного регистра равна разрядности `(q + 1) / 2` и может быть
+    /// меньше заданной. Поэтому при том же ключе гамма отличается от схемы Фибоначчи,
+    /// и дешифровать нужно в той же схеме
+    Galois,
+}
+
+/// Регистр генератора гаммы: с переносом или линейный, в одной из схем
+#[derive(Clone)]
+enum Register {
+    Fibonacci(Fcsr),
+    Galois(BufferedGalois),
+    LinearFibonacci(Lfsr),
+    LinearGalois(GaloisLfsr),
+}
+
+impl Register {
+    fn new(config: RegisterConfig, architecture: Architecture) -> Self {
+        match (config.feedback, architecture) {
+            (Feedback::Carry, Architecture::Fibonacci) => Register::Fibonacci(Fcsr::new(config)),
+            (Feedback::Carry, Architecture::Galois) => {
+                Register::Galois(BufferedGalois::new(GaloisFcsr::from_config(&config)))
+            }
+            (Feedback::Linear, Architecture::Fibonacci) => {
+                Register::LinearFibonacci(Lfsr::new(&config))
+            }
+            (Feedback::Linear, Architecture::Galois) => {
+                Register::LinearGalois(GaloisLfsr::from_config(&config))
+            }
+        }
+    }
+
+    fn next_bit(&mut self) -> u8 {
+        match self {
+            Register::Fibonacci(fcsr) => fcsr.next_bit(),
+            Register::Galois(fcsr) => fcsr.next_bit(),
+            Register::LinearFibonacci(lfsr) => lfsr.next_bit(),
+            Register::LinearGalois(lfsr) => lfsr.next_bit(),
+        }
+    }
+
+    /// Загрузка ключа и синхропосылки в регистр с номером `index`
+    fn load(&mut self, setup: &KeySetup, index: usize) {
+        match self {
+            Register::Fibonacci(fcsr) => {
+                let (state, carry) = setup.register_state(index, fcsr.length, fcsr.taps.len() - 1);
+                fcsr.load(state, carry);
+            }
+            Register::Galois(fcsr) => {
+                let (state, carry) = setup.galois_state(index, fcsr.length(), fcsr.carry_cells());
+                fcsr.load(&state, &carry);
+            }
+            // Без переноса состояние из ключа не бывает нулевым
+            Register::LinearFibonacci(lfsr) => {
+                let (state, _) = setup.register_state(index, lfsr.length(), 0);
+                lfsr.load(state);
+            }
+            Register::LinearGalois(lfsr) => {
+                let (state, _) = setup.register_state(index, lfsr.length(), 0);
+                lfsr.load(&state);
+            }
+        }
+    }
+}
+
+/// Поточный шифр на регистрах сдвига с обратной связью по переносу и линейных регистрах
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    #[command(flatten)]
+    verbosity: Verbosity<WarnLevel>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Шифрование файла или stdin в файл или stdout
+    Encrypt(FileArgs),
+    /// Дешифрование файла или stdin в файл или stdout
+    Decrypt(FileArgs),
+    /// Шифрование и дешифрование по мере набора текста
+    Interactive(KeystreamArgs),
+    /// Выгрузка гаммы для внешних наборов тестов: dieharder, TestU01, PractRand
+    Keystream(KeystreamExportArgs),
+    /// Статистические тесты NIST SP 800-22 для битов гаммы или файла
+    Randtest(RandTestArgs),
+    /// Целые связи и многочлены связи регистров, линейная и 2-адическая сложность гаммы
+    Analyze(AnalyzeArgs),
+    /// Таблица примитивных многочленов или проверка примитивности заданных многочленов
+    Polynomials {
+        /// Многочлены вида `x^5+x^2+1`
+        polynomials: Vec<String>,
+    },
+    /// Восстановление регистра по началу гаммы рациональным приближением
+    Attack(AttackArgs),
+    /// Сравнение скорости выработки гаммы схемами Фибоначчи и Галуа
+    Bench {
+        /// Число битов гаммы на каждый регистр
+        #[arg(long, default_value_t = 1 << 22)]
+        bits: usize,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct FileArgs {
+    /// Входной файл, по умолчанию stdin
+    input_file: Option<PathBuf>,
+    /// Выходной файл, по умолчанию stdout
+    output_file: Option<PathBuf>,
+    #[command(flatten)]
+    keystream: KeystreamArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct KeystreamExportArgs {
+    /// Выходной файл, по умолчанию stdout
+    output_file: Option<PathBuf>,
+    /// Число байтов гаммы, 0 - бесконечный поток для `RNG_test stdin`
+    #[arg(long, default_value_t = 1 << 20)]
+    bytes: usize,
+    /// Формат вывода
+    #[arg(long, value_enum, default_value_t = ExportFormat::Binary)]
+    format: ExportFormat,
+    #[command(flatten)]
+    keystream: KeystreamArgs,
+}
+
+/// Формат выгрузки гаммы, старший бит байта выработан первым
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum ExportFormat {
+    /// Сырые байты: `dieharder -g 201 -f`, `bbattery_*File` из TestU01, `RNG_test stdin`
+    Binary,
+    /// Символы 0 и 1 по одному на бит, как в NIST STS
+    Bits,
+    /// Текстовый формат `dieharder -g 202`: заголовок и 32-битные числа по одному в строке
+    Dieharder,
+}
+
+#[derive(clap::Args, Debug)]
+struct RandTestArgs {
+    /// Число проверяемых битов
+    #[arg(long, default_value_t = 1_000_000)]
+    bits: usize,
+    /// Уровень значимости
+    #[arg(long, default_value_t = 0.01)]
+    alpha: f64,
+    /// Файл с битами другого источника вместо гаммы, старший бит байта первый
+    #[arg(long)]
+    input: Option<PathBuf>,
+    #[command(flatten)]
+    keystream: KeystreamArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct AnalyzeArgs {
+    /// Длина анализируемого начала гаммы в битах
+    #[arg(long, default_value_t = 2048)]
+    bits: usize,
+    /// Вывести профиль линейной сложности гаммы: длину префикса и сложность в каждой строке
+    #[arg(long)]
+    profile: bool,
+    #[command(flatten)]
+    keystream: KeystreamArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct AttackArgs {
+    /// Число наблюдаемых битов гаммы
+    #[arg(long, default_value_t = 1024)]
+    bits: usize,
+    /// Число следующих битов гаммы для проверки восстановленных параметров
+    #[arg(long, default_value_t = 1024)]
+    verify: usize,
+    /// Атаковать выход одного регистра с этим номером, начиная с 1, а не всю гамму
+    #[arg(long)]
+    single: Option<usize>,
+    #[command(flatten)]
+    keystream: KeystreamArgs,
+}
+
+/// Параметры генератора гаммы
+#[derive(clap::Args, Debug)]
+struct KeystreamArgs {
+    /// Именованный набор регистров
+    #[arg(long, value_enum, default_value_t = Preset::Variant7)]
+    preset: Preset,
+    /// Регистр `ДЛИНА:ОТВОДЫ[:СОСТОЯНИЕ]`, например `96:96,95,45,2`, заменяет набор регистров.
+    /// Отводы нумеруются с 1, состояние - строка битов, по умолчанию из одних единиц.
+    /// Линейный регистр задается префиксом `lfsr:`: `lfsr:x^5+x^2+1[:СОСТОЯНИЕ]`,
+    /// `lfsr:32` с примитивным многочленом из таблицы или `lfsr:ДЛИНА:ОТВОДЫ[:СОСТОЯНИЕ]`
+    #[arg(long, value_parser = parse_register, conflicts_with = "config")]
+    register: Vec<RegisterConfig>,
+    /// Файл с описаниями регистров по одному на строку, заменяет набор регистров
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Ключ в шестнадцатеричной записи
+    #[arg(long, conflicts_with = "password")]
+    key: Option<String>,
+    /// Пароль, ключом служит его SHA-256
+    #[arg(long)]
+    password: Option<String>,
+    /// Синхропосылка в шестнадцатеричной записи, разные синхропосылки дают разные гаммы
+    #[arg(long)]
+    nonce: Option<String>,
+    /// Число холостых тактов после загрузки ключа
+    #[arg(long, default_value_t = 512)]
+    warmup: usize,
+    /// Схема регистров
+    #[arg(long, value_enum, default_value_t = Architecture::Fibonacci)]
+    architecture: Architecture,
+    /// Число битов гаммы на символ UTF-8: каждый символ входа шифруется одним словом гаммы,
+    /// шифротекст двоичный
+    #[arg(long, value_enum, default_value_t = SymbolBits::Eight)]
+    symbol_bits: SymbolBits,
+    /// Старый текстовый режим: гаммой изменяется только младший бит кода символа
+    #[arg(long, conflicts_with = "symbol_bits")]
+    legacy_text: bool,
+}
+
+impl KeystreamArgs {
+    /// Разрядность символа байтового режима, `None` в старом текстовом режиме
+    fn byte_symbols(&self) -> Option<SymbolBits> {
+        (!self.legacy_text).then_some(self.symbol_bits)
+    }
+}
+
+/// Разрядность символа байтового режима
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum SymbolBits {
+    #[value(name = "8")]
+    Eight,
+    #[value(name = "16")]
+    Sixteen,
+    #[value(name = "32")]
+    ThirtyTwo,
+}
+
+impl SymbolBits {
+    fn bytes(&self) -> usize {
+        match self {
+            SymbolBits::Eight => 1,
+            SymbolBits::Sixteen => 2,
+            SymbolBits::ThirtyTwo => 4,
+        }
+    }
+}
+
+/// Направление обработки байтов: границы символов определяются по открытому тексту
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+/// Длина символа UTF-8 по первому байту, байт вне UTF-8 считается отдельным символом
+fn utf8_width(lead: u8) -> usize {
+    match lead {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    }
+}
+
+#[derive(Clone)]
+struct Cipher {
+    registers: Vec<Register>,
+}
+
+impl Cipher {
+    /// Регистры по 7 варианту
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::from_registers(Preset::Variant7.registers())
+    }
+
+    pub fn from_registers(registers: Vec<RegisterConfig>) -> Self {
+        Self::with_architecture(registers, Architecture::Fibonacci)
+    }
+
+    pub fn with_architecture(registers: Vec<RegisterConfig>, architecture: Architecture) -> Self {
+        Self {
+            registers: registers
+                .into_iter()
+                .map(|config| Register::new(config, architecture))
+                .collect(),
+        }
+    }
+
+    /// Шифр с состояниями и переносами регистров из ключа и синхропосылки,
+    /// после загрузки отбрасывается `warmup` тактов гаммы
+    pub fn with_key(
+        registers: Vec<RegisterConfig>,
+        architecture: Architecture,
+        setup: &KeySetup,
+    ) -> Self {
+        let mut cipher = Self::with_architecture(registers, architecture);
+        for (index, register) in cipher.registers.iter_mut().enumerate() {
+            register.load(setup, index);
+        }
+        for _ in 0..setup.warmup {
+            cipher.generate_gamma();
+        }
+        cipher
+    }
+
+    fn generate_gamma(&mut self) -> u32 {
+        // xor выходов всех регистров
+        self.registers
+            .iter_mut()
+            .fold(0, |gamma, fcsr| gamma ^ fcsr.next_bit()) as u32
+    }
+
+    pub fn process_char(&mut self, ch: char) -> char {
+        let gamma = self.generate_gamma();
+        std::char::from_u32((ch as u32) ^ gamma).expect("invalid char")
+    }
+
+    /// Слово гаммы из `bits` последовательных битов, первый бит старший
+    fn next_word(&mut self, bits: SymbolBits) -> u32 {
+        (0..bits.bytes() * 8).fold(0, |word, _| (word << 1) | self.generate_gamma())
+    }
+
+    /// Шифрование символов UTF-8 по одному слову гаммы из `bits` битов на символ. Старший байт
+    /// слова накладывается на первый байт символа, байты символа длиннее слова закрываются
+    /// байтами слова по кругу. Длина символа берется из первого байта открытого текста,
+    /// поэтому при дешифровании он сначала расшифровывается
+    pub fn process_bytes(
+        &mut self,
+        data: &[u8],
+        bits: SymbolBits,
+        direction: Direction,
+    ) -> Vec<u8> {
+        let n = bits.bytes();
+        let mut ans = Vec::with_capacity(data.len());
+        let mut rest = data;
+        while let Some(lead) = rest.first() {
+            let word = self.next_word(bits).to_be_bytes();
+            let word = &word[4 - n..];
+            let plain_lead = match direction {
+                Direction::Encrypt => *lead,
+                Direction::Decrypt => lead ^ word[0],
+            };
+            let (symbol, tail) = rest.split_at(utf8_width(plain_lead).min(rest.len()));
+            ans.extend(symbol.iter().zip(word.iter().cycle()).map(|(b, g)| b ^ g));
+            rest = tail;
+        }
+        ans
+    }
+
+    pub fn process_str(&mut self, text: &str) -> String {
+        let mut result = Vec::new();
+        for ch in text.chars() {
+            result.push(self.process_char(ch));
+        }
+        result.iter().collect()
+    }
+}
+
+/// Регистры из файла, флагов `--register` или набора, `None` при ошибке в файле
+fn register_configs(args: &KeystreamArgs) -> Option<Vec<RegisterConfig>> {
+    let registers = match &args.config {
+        Some(config) => match read_config(config) {
+            Ok(registers) => registers,
+            Err(e) => {
+                error!("Invalid register config: {e}");
+                return None;
+            }
+        },
+        None if !args.register.is_empty() => args.register.clone(),
+        None => args.preset.registers(),
+    };
+    for register in &registers {
+        debug!(
+            "Register of length {} with taps {:?}",
+            register.length, register.taps
+        );
+    }
+    Some(registers)
+}
+
+/// Шифр по параметрам генератора гаммы, `None` при ошибке в параметрах
+fn build_cipher(args: KeystreamArgs) -> Option<Cipher> {
+    let registers = register_configs(&args)?;
+    let key = match (args.key, args.password) {
+        (Some(key), _) => match parse_hex(&key) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                error!("Invalid key: {e}");
+                return None;
+            }
+        },
+        (None, Some(password)) => Some(password_key(&password)),
+        (None, None) => None,
+    };
+    let nonce = match args.nonce.as_deref().map(parse_hex).transpose() {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            error!("Invalid nonce: {e}");
+            return None;
+        }
+    };
+    match key {
+        Some(key) => Some(Cipher::with_key(
+            registers,
+            args.architecture,
+            &KeySetup {
+                key,
+                nonce: nonce.unwrap_or_default(),
+                warmup: args.warmup,
+            },
+        )),
+        None => {
+            if nonce.is_some() {
+                error!("--nonce requires --key or --password");
+                return None;
+            }
+            warn!("Key wasn't specified, using initial register states from configuration");
+            Some(Cipher::with_architecture(registers, args.architecture))
+        }
+    }
+}
+
+/// Обработка файла или stdin: на вход накладывается гамма. В старом текстовом режиме
+/// шифрование и дешифрование совпадают, а вход должен быть в UTF-8
+fn process_file(args: FileArgs, direction: Direction) -> ExitCode {
+    let bits = args.keystream.byte_symbols();
+    let Some(mut cipher) = build_cipher(args.keystream) else {
+        return ExitCode::from(1);
+    };
+    let content = match &args.input_file {
+        Some(input_file) => fs::read(input_file),
+        None => {
+            let mut content = Vec::new();
+            io::stdin().read_to_end(&mut content).map(|_| content)
+        }
+    };
+    let content = match content {
+        Ok(content) => content,
+        Err(e) => {
+            error!("Failed to read input {e}");
+            return ExitCode::from(1);
+        }
+    };
+    let result = match bits {
+        Some(bits) => cipher.process_bytes(&content, bits, direction),
+        None => match String::from_utf8(content) {
+            Ok(text) => cipher.process_str(&text).into_bytes(),
+            Err(_) => {
+                error!("Legacy text mode requires UTF-8 input");
+                return ExitCode::from(1);
+            }
+        },
+    };
+    debug!("Processed {} bytes", result.len());
+    let written = match args.output_file {
+        Some(output_file) => fs::write(output_file, &result),
+        None => io::stdout().write_all(&result),
+    };
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("Failed to write output {e}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Запись гаммы в выбранном формате
+fn write_keystream<W: Write>(
+    mut writer: W,
+    cipher: &mut Cipher,
+    bytes: usize,
+    format: ExportFormat,
+) -> io::Result<()> {
+    let endless = bytes == 0;
+    match format {
+        ExportFormat::Binary | ExportFormat::Bits => {
+            let mut written = 0;
+            while endless || written < bytes {
+                let byte = cipher.next_word(SymbolBits::Eight) as u8;
+                match format {
+                    ExportFormat::Binary => writer.write_all(&[byte])?,
+                    _ => write!(writer, "{byte:08b}")?,
+                }
+                written += 1;
+            }
+        }
+        ExportFormat::Dieharder => {
+            if endless {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "dieharder format needs a finite number of bytes",
+                ));
+            }
+            let count = bytes / 4;
+            writeln!(writer, "type: d\ncount: {count}\nnumbit: 32")?;
+            for _ in 0..count {
+                writeln!(writer, "{}", cipher.next_word(SymbolBits::ThirtyTwo))?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+fn export_keystream(args: KeystreamExportArgs) -> ExitCode {
+    let Some(mut cipher) = build_cipher(args.keystream) else {
+        return ExitCode::from(1);
+    };
+    let written = match args.output_file {
+        Some(output_file) => File::create(output_file).and_then(|file| {
+            write_keystream(BufWriter::new(file), &mut cipher, args.bytes, args.format)
+        }),
+        None => write_keystream(
+            BufWriter::new(io::stdout().lock()),
+            &mut cipher,
+            args.bytes,
+            args.format,
+        ),
+    };
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        // Читатель бесконечного потока закрыл канал
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("Failed to write keystream {e}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Статистические тесты битов гаммы или файла, результат каждого теста в отдельной строке
+fn randtest(args: RandTestArgs) -> ExitCode {
+    if !(0.0..1.0).contains(&args.alpha) {
+        error!("Significance level must be in [0, 1)");
+        return ExitCode::from(1);
+    }
+    let bits: Vec<u8> = match &args.input {
+        Some(input) => match fs::read(input) {
+            Ok(content) => content
+                .iter()
+                .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1))
+                .take(args.bits)
+                .collect(),
+            Err(e) => {
+                error!("Failed to read input file {e}");
+                return ExitCode::from(1);
+            }
+        },
+        None => {
+            let Some(mut cipher) = build_cipher(args.keystream) else {
+                return ExitCode::from(1);
+            };
+            (0..args.bits)
+                .map(|_| cipher.generate_gamma() as u8)
+                .collect()
+        }
+    };
+    if bits.len() < args.bits {
+        warn!("Only {} bits available", bits.len());
+    }
+    for result in randtest::run_all(&bits) {
+        let (p_values, verdict) = match (&result.p_values, result.passed(args.alpha)) {
+            (Some(p_values), Some(passed)) => {
+                let p_values: Vec<String> = p_values.iter().map(|p| format!("{p:.6}")).collect();
+                (p_values.join(" "), if passed { "PASS" } else { "FAIL" })
+            }
+            _ => (String::from("-"), "SKIP"),
+        };
+        println!("{:<28}{verdict:<6}{p_values}", result.name);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Целое связи регистра с переносом и признак l-последовательности или многочлен связи
+/// линейного регистра и его примитивность, затем линейная и 2-адическая сложность начала
+/// выхода каждого регистра и всей гаммы
+fn analyze(args: AnalyzeArgs) -> ExitCode {
+    let Some(configs) = register_configs(&args.keystream) else {
+        return ExitCode::from(1);
+    };
+    for (i, config) in configs.iter().enumerate() {
+        if config.feedback == Feedback::Linear {
+            let period = match Polynomial::from_exponents(&config.connection_exponents()) {
+                Some(c) if c.is_primitive() => {
+                    format!("C(x) = {c}, primitive, period 2^{} - 1", c.degree())
+                }
+                Some(c) => format!("C(x) = {c}, not primitive"),
+                None => String::from("primitivity is checked up to degree 127"),
+            };
+            println!("Register {}: linear, {period}", i + 1);
+            continue;
+        }
+        let q = config.connection_integer();
+        let prime = complexity::is_prime(&q);
+        let period = if complexity::is_l_sequence(&q) {
+            format!("l-sequence, period {}", &q - 1)
+        } else {
+            String::from("not an l-sequence")
+        };
+        println!(
+            "Register {}: q = {q} ({} bits), {}, {period}",
+            i + 1,
+            q.bits(),
+            if prime { "prime" } else { "composite" },
+        );
+    }
+    let Some(cipher) = build_cipher(args.keystream) else {
+        return ExitCode::from(1);
+    };
+    let mut sequences: Vec<(String, Vec<u8>)> = cipher
+        .registers
+        .iter()
+        .enumerate()
+        .map(|(i, register)| {
+            let mut register = register.clone();
+            let bits = (0..args.bits).map(|_| register.next_bit()).collect();
+            (format!("register {}", i + 1), bits)
+        })
+        .collect();
+    let mut keystream = cipher.clone();
+    let bits = (0..args.bits)
+        .map(|_| keystream.generate_gamma() as u8)
+        .collect();
+    sequences.push((String::from("keystream"), bits));
+    println!("{:<12}{:>10}{:>10}", "sequence", "linear", "2-adic");
+    for (name, bits) in &sequences {
+        let linear = complexity::linear_complexity_profile(bits)
+            .last()
+            .copied()
+            .unwrap_or(0);
+        let two_adic = complexity::two_adic_complexity(bits);
+        println!("{name:<12}{linear:>10}{two_adic:>10.1}");
+    }
+    debug!(
+        "Random sequence of {} bits has linear complexity about {}",
+        args.bits,
+        args.bits / 2
+    );
+    if args.profile {
+        let (_, bits) = sequences.last().expect("keystream");
+        for (n, l) in complexity::linear_complexity_profile(bits)
+            .iter()
+            .enumerate()
+        {
+            println!("{} {l}", n + 1);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Атака рациональным приближением на начало гаммы или выхода одного регистра.
+/// Найденные параметры проверяются на следующих, не использованных в атаке битах
+fn attack_keystream(args: AttackArgs) -> ExitCode {
+    let Some(mut cipher) = build_cipher(args.keystream) else {
+        return ExitCode::from(1);
+    };
+    let total = args.bits + args.verify;
+    let bits: Vec<u8> = match args.single {
+        Some(index) => {
+            let Some(register) = index.checked_sub(1).and_then(|i| cipher.registers.get(i)) else {
+                error!(
+                    "No register {index}, the cipher has {}",
+                    cipher.registers.len()
+                );
+                return ExitCode::from(1);
+            };
+            let mut register = register.clone();
+            (0..total).map(|_| register.next_bit()).collect()
+        }
+        None => (0..total).map(|_| cipher.generate_gamma() as u8).collect(),
+    };
+    let recovered = attack::rational_attack(&bits[..args.bits]);
+    println!(
+        "Approximation stable after {} of {} bits",
+        recovered.bits_needed, args.bits
+    );
+    if 2 * recovered.bits_needed > args.bits {
+        warn!("Approximation may still change, observe more bits");
+    }
+    println!("q = {} ({} bits)", recovered.q, recovered.q.bits());
+    println!("p = {}", recovered.p);
+    println!("2-adic complexity: {:.1}", recovered.complexity());
+    let regenerated = match &recovered.register {
+        Some(register) => {
+            println!(
+                "Register: {}, carry {}, output from bit {}",
+                register.config, register.carry, register.offset
+            );
+            register.regenerate(total)
+        }
+        None => {
+            println!("Register: not recovered");
+            recovered.regenerate(total)
+        }
+    };
+    match regenerated.iter().zip(&bits).position(|(a, b)| a != b) {
+        None => println!(
+            "Verification: all {total} bits regenerated, {} of them unseen",
+            args.verify
+        ),
+        Some(i) => println!("Verification: failed at bit {i}"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Таблица примитивных многочленов или проверка заданных многочленов
+fn polynomials(polynomials: Vec<String>) -> ExitCode {
+    if polynomials.is_empty() {
+        for degree in lfsr::primitive_degrees() {
+            let exponents = lfsr::primitive_polynomial(degree).expect("table degree");
+            let polynomial = Polynomial::from_exponents(&exponents).expect("degree up to 127");
+            println!("{degree:>4}  {polynomial}");
+        }
+        return ExitCode::SUCCESS;
+    }
+    for s in polynomials {
+        let exponents = match lfsr::parse_polynomial(&s) {
+            Ok(exponents) => exponents,
+            Err(e) => {
+                error!("Invalid polynomial: {e}");
+                return ExitCode::from(1);
+            }
+        };
+        let Some(polynomial) = Polynomial::from_exponents(&exponents) else {
+            error!("Polynomial degree exceeds {}: {s}", Polynomial::MAX_DEGREE);
+            return ExitCode::from(1);
+        };
+        let verdict = if polynomial.is_primitive() {
+            "primitive"
+        } else {
+            "not primitive"
+        };
+        println!("{polynomial}: {verdict}");
+    }
+    ExitCode::SUCCESS
+}
+
+/// Скорость выработки `bits` битов каждым регистром набора по 7 варианту в обеих схемах
+fn bench(bits: usize) -> ExitCode {
+    let mut rates = Vec::new();
+    for architecture in [Architecture::Fibonacci, Architecture::Galois] {
+        let start = Instant::now();
+        for config in Preset::Variant7.registers() {
+            // Тот же путь, что и при шифровании: регистр Галуа выдает биты из слов
+            let mut register = Register::new(config, architecture);
+            for _ in 0..bits {
+                black_box(register.next_bit());
+            }
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        let rate = (3 * bits) as f64 / elapsed / 1e6;
+        println!("{architecture:?}: {elapsed:.3} s, {rate:.2} Mbit/s");
+        rates.push(rate);
+    }
+    println!("Speedup: {:.2}x", rates[1] / rates[0]);
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    env_logger::Builder::new()
+        .filter_level(cli.verbosity.log_level_filter())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
+    match cli.command {
+        Command::Encrypt(args) => process_file(args, Direction::Encrypt),
+        Command::Decrypt(args) => process_file(args, Direction::Decrypt),
+        Command::Keystream(args) => export_keystream(args),
+        Command::Randtest(args) => randtest(args),
+        Command::Analyze(args) => analyze(args),
+        Command::Attack(args) => attack_keystream(args),
+        Command::Polynomials { polynomials: p } => polynomials(p),
+        Command::Bench { bits } => bench(bits),
+        Command::Interactive(args) => {
+            let bits = args.byte_symbols();
+            let Some(cipher) = build_cipher(args) else {
+                return ExitCode::from(1);
+            };
+            match interactive::run(&cipher, bits) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    error!("Interactive mode requires a terminal: {e}");
+                    ExitCode::from(1)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{parse_register, Preset};
+    use crate::key::KeySetup;
+    use crate::{
+        write_keystream, Architecture, Cipher, Cli, Command, Direction, ExportFormat, SymbolBits,
+    };
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn randtest_bits() {
+        let cli = Cli::parse_from(["lab3", "randtest", "--bits", "1000", "--symbol-bits", "8"]);
+        let Command::Randtest(args) = cli.command else {
+            panic!("randtest expected");
+        };
+        assert_eq!(args.bits, 1000);
+        assert!(matches!(args.keystream.symbol_bits, SymbolBits::Eight));
+    }
+
+    #[test]
+    fn analyze_bits() {
+        let cli = Cli::parse_from(["lab3", "analyze", "--bits", "256", "--profile"]);
+        let Command::Analyze(args) = cli.command else {
+            panic!("analyze expected");
+        };
+        assert_eq!(args.bits, 256);
+        assert!(args.profile);
+        assert!(matches!(
+            args.keystream.byte_symbols(),
+            Some(SymbolBits::Eight)
+        ));
+    }
+
+    #[test]
+    fn attack_bits() {
+        let cli = Cli::parse_from(["lab3", "attack", "--bits", "400", "--single", "2"]);
+        let Command::Attack(args) = cli.command else {
+            panic!("attack expected");
+        };
+        assert_eq!((args.bits, args.verify, args.single), (400, 1024, Some(2)));
+    }
+
+    #[test]
+    fn legacy_text_flag() {
+        let cli = Cli::parse_from(["lab3", "encrypt", "--legacy-text"]);
+        let Command::Encrypt(args) = cli.command else {
+            panic!("encrypt expected");
+        };
+        assert!(args.keystream.byte_symbols().is_none());
+        let legacy_with_bits = ["lab3", "decrypt", "--legacy-text", "--symbol-bits", "16"];
+        assert!(Cli::try_parse_from(legacy_with_bits).is_err());
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let text = "Привет, Rust пока ***@@@ жизнь! :(";
+        let mut cipher = Cipher::new();
+        let enc = cipher.process_str(text);
+        let mut cipher = Cipher::new();
+        let dec = cipher.process_str(enc.as_str());
+        assert_eq!(text, dec);
+    }
+
+    #[test]
+    fn encrypt_and_decrypt2() {
+        let text = "ven-ig3-2tr345g 4 134 4y  f  kf13ык 32 345 р5кнт кл ё2о 353 ";
+        let mut cipher = Cipher::new();
+        let enc = cipher.process_str(text);
+        let mut cipher = Cipher::new();
+        let dec = cipher.process_str(enc.as_str());
+        assert_eq!(text, dec);
+    }
+
+    #[test]
+    fn configured_registers() {
+        let registers = ["5:5,3:10110", "7:7,1"]
+            .into_iter()
+            .map(|s| parse_register(s).unwrap())
+            .collect::<Vec<_>>();
+        let text = "Короткие регистры";
+        let enc = Cipher::from_registers(registers.clone()).process_str(text);
+        assert_ne!(enc, text);
+        assert_eq!(Cipher::from_registers(registers).process_str(&enc), text);
+    }
+
+    fn keyed(key: &[u8], nonce: &[u8]) -> Cipher {
+        let setup = KeySetup {
+            key: key.to_vec(),
+            nonce: nonce.to_vec(),
+            warmup: 64,
+        };
+        Cipher::with_key(
+            Preset::Variant7.registers(),
+            Architecture::Fibonacci,
+            &setup,
+        )
+    }
+
+    fn gamma(cipher: &mut Cipher, len: usize) -> Vec<u32> {
+        (0..len).map(|_| cipher.generate_gamma()).collect()
+    }
+
+    #[test]
+    fn keyed_encrypt_and_decrypt() {
+        let text = "Ключ и синхропосылка";
+        let enc = keyed(b"key", &[1]).process_str(text);
+        assert_ne!(enc, Cipher::new().process_str(text));
+        assert_eq!(keyed(b"key", &[1]).process_str(&enc), text);
+    }
+
+    #[test]
+    fn distinct_nonces() {
+        let first = gamma(&mut keyed(b"key", &[0]), 256);
+        assert_eq!(first, gamma(&mut keyed(b"key", &[0]), 256));
+        assert_ne!(first, gamma(&mut keyed(b"key", &[1]), 256));
+        assert_ne!(first, gamma(&mut keyed(b"key", &[0, 0]), 256));
+        assert_ne!(first, gamma(&mut keyed(b"other", &[0]), 256));
+    }
+
+    #[test]
+    fn byte_keystream() {
+        let text = "Байты UTF-8, 🦀";
+        for bits in [
+            SymbolBits::Eight,
+            SymbolBits::Sixteen,
+            SymbolBits::ThirtyTwo,
+        ] {
+            let enc = keyed(b"key", &[2]).process_bytes(text.as_bytes(), bits, Direction::Encrypt);
+            assert_eq!(enc.len(), text.len());
+            // Изменяются не только младшие биты
+            assert!(enc
+                .iter()
+                .zip(text.as_bytes())
+                .any(|(e, t)| (e ^ t) & 0xfe != 0));
+            let dec = keyed(b"key", &[2]).process_bytes(&enc, bits, Direction::Decrypt);
+            assert_eq!(dec, text.as_bytes());
+        }
+    }
+
+    #[test]
+    fn word_per_symbol() {
+        let text = "Слово на символ";
+        let encrypt =
+            |bits| keyed(b"key", &[2]).process_bytes(text.as_bytes(), bits, Direction::Encrypt);
+        let (eight, sixteen, thirty_two) = (
+            encrypt(SymbolBits::Eight),
+            encrypt(SymbolBits::Sixteen),
+            encrypt(SymbolBits::ThirtyTwo),
+        );
+        assert_ne!(eight, sixteen);
+        assert_ne!(sixteen, thirty_two);
+        // Двухбайтная буква закрывается одним байтом гаммы, второй символ - следующим
+        let mut cipher = keyed(b"key", &[2]);
+        let first = cipher.next_word(SymbolBits::Eight) as u8;
+        let second = cipher.next_word(SymbolBits::Eight) as u8;
+        let bytes = text.as_bytes();
+        assert_eq!(
+            eight[..4],
+            [
+                bytes[0] ^ first,
+                bytes[1] ^ first,
+                bytes[2] ^ second,
+                bytes[3] ^ second
+            ]
+        );
+        // Байты вне UTF-8 шифруются как отдельные символы
+        let binary = [0xff, 0x80, 0xe0, 0x00];
+        let enc =
+            keyed(b"key", &[2]).process_bytes(&binary, SymbolBits::Sixteen, Direction::Encrypt);
+        let dec = keyed(b"key", &[2]).process_bytes(&enc, SymbolBits::Sixteen, Direction::Decrypt);
+        assert_eq!(dec, binary);
+    }
+
+    #[test]
+    fn word_bits_order() {
+        let mut first = keyed(b"key", &[3]);
+        let bits = gamma(&mut first, 16);
+        let mut second = keyed(b"key", &[3]);
+        let word = second.next_word(SymbolBits::Sixteen);
+        assert_eq!(word, bits.iter().fold(0, |w, b| (w << 1) | b));
+    }
+
+    #[test]
+    fn galois_cipher() {
+        let text = "Схема Галуа";
+        let registers = Preset::Variant7.registers();
+        let enc = Cipher::with_architecture(registers.clone(), Architecture::Galois).process_bytes(
+            text.as_bytes(),
+            SymbolBits::Eight,
+            Direction::Encrypt,
+        );
+        let dec = Cipher::with_architecture(registers, Architecture::Galois).process_bytes(
+            &enc,
+            SymbolBits::Eight,
+            Direction::Decrypt,
+        );
+        assert_eq!(dec, text.as_bytes());
+    }
+
+    #[test]
+    fn mixed_registers() {
+        let registers: Vec<_> = ["lfsr:x^31+x^3+1", "96:96,95,45,2", "lfsr:17"]
+            .into_iter()
+            .map(|s| parse_register(s).unwrap())
+            .collect();
+        let text = "Линейные и с переносом";
+        for architecture in [Architecture::Fibonacci, Architecture::Galois] {
+            let setup = KeySetup {
+                key: b"key".to_vec(),
+                nonce: vec![5],
+                warmup: 64,
+            };
+            let enc = Cipher::with_key(registers.clone(), architecture, &setup).process_bytes(
+                text.as_bytes(),
+                SymbolBits::Eight,
+                Direction::Encrypt,
+            );
+            assert_ne!(enc, text.as_bytes());
+            let dec = Cipher::with_key(registers.clone(), architecture, &setup).process_bytes(
+                &enc,
+                SymbolBits::Eight,
+                Direction::Decrypt,
+            );
+            assert_eq!(dec, text.as_bytes());
+        }
+    }
+
+    #[test]
+    fn keystream_formats() {
+        let mut binary = Vec::new();
+        write_keystream(
+            &mut binary,
+            &mut keyed(b"key", &[4]),
+            8,
+            ExportFormat::Binary,
+        )
+        .unwrap();
+        assert_eq!(binary.len(), 8);
+        let mut bits = Vec::new();
+        write_keystream(&mut bits, &mut keyed(b"key", &[4]), 8, ExportFormat::Bits).unwrap();
+        let expected: String = binary.iter().map(|b| format!("{b:08b}")).collect();
+        assert_eq!(String::from_utf8(bits).unwrap(), expected);
+        let mut dieharder = Vec::new();
+        write_keystream(
+            &mut dieharder,
+            &mut keyed(b"key", &[4]),
+            8,
+            ExportFormat::Dieharder,
+        )
+        .unwrap();
+        let dieharder = String::from_utf8(dieharder).unwrap();
+        let lines: Vec<&str> = dieharder.lines().collect();
+        assert_eq!(lines[..3], ["type: d", "count: 2", "numbit: 32"]);
+        let first = u32::from_be_bytes(binary[..4].try_into().unwrap());
+        assert_eq!(lines[3], first.to_string());
+    }
+}
@@ -0,0 +1,335 @@
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use statrs::function::erf::erfc;
+use statrs::function::gamma::gamma_ur;
+use std::f64::consts::SQRT_2;
+
+/// Дополнительная неполная гамма-функция `igamc` из NIST SP 800-22
+fn igamc(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    gamma_ur(a, x)
+}
+
+/// Функция стандартного нормального распределения
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+/// Результат одного теста: p-значения (у некоторых тестов их несколько)
+/// или `None`, если последовательность слишком коротка для теста
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: &'static str,
+    pub p_values: Option<Vec<f64>>,
+}
+
+impl TestResult {
+    /// Тест пройден, если все p-значения не меньше уровня значимости
+    pub fn passed(&self, alpha: f64) -> Option<bool> {
+        self.p_values
+            .as_ref()
+            .map(|p| p.iter().all(|p| *p >= alpha))
+    }
+}
+
+/// Частотный тест: доля единиц во всей последовательности
+pub fn monobit(bits: &[u8]) -> Option<f64> {
+    if bits.is_empty() {
+        return None;
+    }
+    let sum: i64 = bits.iter().map(|b| 2 * *b as i64 - 1).sum();
+    let s_obs = sum.abs() as f64 / (bits.len() as f64).sqrt();
+    Some(erfc(s_obs / SQRT_2))
+}
+
+/// Частотный тест в блоках длины `m`
+pub fn block_frequency(bits: &[u8], m: usize) -> Option<f64> {
+    let blocks = bits.len() / m;
+    if m == 0 || blocks == 0 {
+        return None;
+    }
+    let chi: f64 = bits
+        .chunks_exact(m)
+        .map(|block| {
+            let pi = block.iter().map(|b| *b as f64).sum::<f64>() / m as f64;
+            (pi - 0.5).powi(2)
+        })
+        .sum::<f64>()
+        * 4.0
+        * m as f64;
+    Some(igamc(blocks as f64 / 2.0, chi / 2.0))
+}
+
+/// Тест на серии: число смен значения бита. При сильном перекосе частот
+/// тест не проводится и p-значение равно нулю
+pub fn runs(bits: &[u8]) -> Option<f64> {
+    let n = bits.len() as f64;
+    if bits.len() < 2 {
+        return None;
+    }
+    let pi = bits.iter().map(|b| *b as f64).sum::<f64>() / n;
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return Some(0.0);
+    }
+    let v = 1 + bits.windows(2).filter(|w| w[0] != w[1]).count();
+    let p = erfc(
+        (v as f64 - 2.0 * n * pi * (1.0 - pi)).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)),
+    );
+    Some(p)
+}
+
+/// Тест на самую длинную серию единиц в блоке
+pub fn longest_run(bits: &[u8]) -> Option<f64> {
+    // Длина блока, границы классов длин серий и их вероятности из SP 800-22
+    let (m, min, max, pi): (usize, usize, usize, &[f64]) = match bits.len() {
+        0..=127 => return None,
+        128..=6271 => (8, 1, 4, &[0.2148, 0.3672, 0.2305, 0.1875]),
+        6272..=749_999 => (128, 4, 9, &[0.1174, 0.2430, 0.2493, 0.1752, 0.1027, 0.1124]),
+        _ => (
+            10_000,
+            10,
+            16,
+            &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+        ),
+    };
+    let mut v = vec![0usize; pi.len()];
+    let blocks = bits.len() / m;
+    for block in bits.chunks_exact(m) {
+        let (mut longest, mut current) = (0, 0);
+        for bit in block {
+            current = if *bit == 1 { current + 1 } else { 0 };
+            longest = longest.max(current);
+        }
+        v[longest.clamp(min, max) - min] += 1;
+    }
+    let chi: f64 = v
+        .iter()
+        .zip(pi)
+        .map(|(v, pi)| {
+            let expected = blocks as f64 * pi;
+            (*v as f64 - expected).powi(2) / expected
+        })
+        .sum();
+    Some(igamc((pi.len() - 1) as f64 / 2.0, chi / 2.0))
+}
+
+/// Частоты всех перекрывающихся шаблонов длины `m` в последовательности, дополненной
+/// ее первыми `m - 1` битами
+fn pattern_counts(bits: &[u8], m: usize) -> Vec<usize> {
+    let mut counts = vec![0; 1 << m];
+    if m == 0 {
+        counts[0] = bits.len();
+        return counts;
+    }
+    let mask = (1 << m) - 1;
+    let mut pattern = 0;
+    for (i, bit) in bits.iter().chain(&bits[..m - 1]).enumerate() {
+        pattern = ((pattern << 1) | *bit as usize) & mask;
+        if i + 1 >= m {
+            counts[pattern] += 1;
+        }
+    }
+    counts
+}
+
+fn psi_squared(bits: &[u8], m: usize) -> f64 {
+    let n = bits.len() as f64;
+    let sum: f64 = pattern_counts(bits, m)
+        .iter()
+        .map(|c| (*c as f64).powi(2))
+        .sum();
+    (1u64 << m) as f64 / n * sum - n
+}
+
+/// Последовательный тест: равномерность перекрывающихся шаблонов длины `m`
+pub fn serial(bits: &[u8], m: usize) -> Option<[f64; 2]> {
+    if m < 2 || bits.len() < m {
+        return None;
+    }
+    let psi = [
+        psi_squared(bits, m),
+        psi_squared(bits, m - 1),
+        psi_squared(bits, m - 2),
+    ];
+    let del1 = psi[0] - psi[1];
+    let del2 = psi[0] - 2.0 * psi[1] + psi[2];
+    Some([
+        igamc((1u64 << (m - 1)) as f64 / 2.0, del1 / 2.0),
+        igamc((1u64 << (m - 2)) as f64 / 2.0, del2 / 2.0),
+    ])
+}
+
+fn phi(bits: &[u8], m: usize) -> f64 {
+    let n = bits.len() as f64;
+    pattern_counts(bits, m)
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let pi = *c as f64 / n;
+            pi * pi.ln()
+        })
+        .sum()
+}
+
+/// Тест приближенной энтропии для шаблонов длины `m` и `m + 1`
+pub fn approximate_entropy(bits: &[u8], m: usize) -> Option<f64> {
+    if m == 0 || bits.len() <= m {
+        return None;
+    }
+    let n = bits.len() as f64;
+    let ap_en = phi(bits, m) - phi(bits, m + 1);
+    let chi = 2.0 * n * (2f64.ln() - ap_en);
+    Some(igamc((1u64 << (m - 1)) as f64, chi / 2.0))
+}
+
+fn cusum_p_value(steps: impl Iterator<Item = u8>, n: usize) -> f64 {
+    let (mut sum, mut z) = (0i64, 0i64);
+    for bit in steps {
+        sum += 2 * bit as i64 - 1;
+        z = z.max(sum.abs());
+    }
+    let (n, z) = (n as f64, z.max(1) as f64);
+    let sqrt_n = n.sqrt();
+    let term = |a: f64, b: f64, from: f64, to: f64| {
+        ((from as i64)..=(to as i64))
+            .map(|k| {
+                let k = k as f64;
+                normal_cdf((4.0 * k + a) * z / sqrt_n) - normal_cdf((4.0 * k + b) * z / sqrt_n)
+            })
+            .sum::<f64>()
+    };
+    1.0 - term(1.0, -1.0, (-n / z + 1.0) / 4.0, (n / z - 1.0) / 4.0)
+        + term(3.0, 1.0, (-n / z - 3.0) / 4.0, (n / z - 1.0) / 4.0)
+}
+
+/// Тест кумулятивных сумм в прямом и обратном направлении
+pub fn cumulative_sums(bits: &[u8]) -> Option<[f64; 2]> {
+    if bits.is_empty() {
+        return None;
+    }
+    Some([
+        cusum_p_value(bits.iter().copied(), bits.len()),
+        cusum_p_value(bits.iter().rev().copied(), bits.len()),
+    ])
+}
+
+/// Спектральный тест: доля пиков дискретного преобразования Фурье ниже порога 95%
+pub fn spectral(bits: &[u8]) -> Option<f64> {
+    let n = bits.len();
+    if n < 2 {
+        return None;
+    }
+    let mut spectrum: Vec<Complex<f64>> = bits
+        .iter()
+        .map(|b| Complex::new(2.0 * *b as f64 - 1.0, 0.0))
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+    let n = n as f64;
+    let threshold = ((1.0 / 0.05f64).ln() * n).sqrt();
+    let n0 = 0.95 * n / 2.0;
+    let n1 = spectrum[..bits.len() / 2]
+        .iter()
+        .filter(|s| s.norm() < threshold)
+        .count() as f64;
+    let d = (n1 - n0) / (n * 0.95 * 0.05 / 4.0).sqrt();
+    Some(erfc(d.abs() / SQRT_2))
+}
+
+/// Набор тестов с параметрами, подобранными по длине последовательности как в SP 800-22
+pub fn run_all(bits: &[u8]) -> Vec<TestResult> {
+    let log2 = (bits.len().max(1) as f64).log2().floor() as usize;
+    let serial_m = 16.min(log2.saturating_sub(3));
+    let entropy_m = 10.min(log2.saturating_sub(6));
+    vec![
+        TestResult {
+            name: "Frequency (monobit)",
+            p_values: monobit(bits).map(|p| vec![p]),
+        },
+        TestResult {
+            name: "Block frequency (M = 128)",
+            p_values: block_frequency(bits, 128).map(|p| vec![p]),
+        },
+        TestResult {
+            name: "Runs",
+            p_values: runs(bits).map(|p| vec![p]),
+        },
+        TestResult {
+            name: "Longest run of ones",
+            p_values: longest_run(bits).map(|p| vec![p]),
+        },
+        TestResult {
+            name: "Serial",
+            p_values: serial(bits, serial_m).map(Vec::from),
+        },
+        TestResult {
+            name: "Approximate entropy",
+            p_values: approximate_entropy(bits, entropy_m).map(|p| vec![p]),
+        },
+        TestResult {
+            name: "Cumulative sums",
+            p_values: cumulative_sums(bits).map(Vec::from),
+        },
+        TestResult {
+            name: "Spectral (DFT)",
+            p_values: spectral(bits).map(|p| vec![p]),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Примеры из NIST SP 800-22 rev. 1a
+    fn bits(s: &str) -> Vec<u8> {
+        s.bytes().map(|b| b - b'0').collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{actual} differs from {expected}"
+        );
+    }
+
+    #[test]
+    fn nist_examples() {
+        assert_close(monobit(&bits("1011010101")).unwrap(), 0.527089);
+        assert_close(block_frequency(&bits("0110011010"), 3).unwrap(), 0.801252);
+        assert_close(runs(&bits("1001101011")).unwrap(), 0.147232);
+        let [p1, p2] = serial(&bits("0011011101"), 3).unwrap();
+        assert_close(p1, 0.808792);
+        assert_close(p2, 0.670320);
+        assert_close(
+            approximate_entropy(&bits("0100110101"), 3).unwrap(),
+            0.261961,
+        );
+        assert_close(cumulative_sums(&bits("1011010111")).unwrap()[0], 0.4116588);
+    }
+
+    #[test]
+    fn spectral_example() {
+        // Все пять пиков 0, 2, 4.47, 2, 4.47 ниже порога 5.47, поэтому N1 = 5. Значение
+        // 0.029523 из примера SP 800-22 получено для N1 = 4 и с этим расчетом не сходится
+        assert_close(spectral(&bits("1001010011")).unwrap(), 0.468160);
+    }
+
+    #[test]
+    fn nist_longest_run() {
+        let epsilon = bits(
+            "11001100000101010110110001001100111000000000001001001101010100\
+             010001001111010110100000001101011111001100111001101101100010110010",
+        );
+        assert_close(longest_run(&epsilon).unwrap(), 0.180609);
+    }
+
+    #[test]
+    fn constant_sequence_fails() {
+        let zeros = vec![0u8; 10_000];
+        for result in run_all(&zeros) {
+            assert_eq!(result.passed(0.01), Some(false), "{}", result.name);
+        }
+    }
+}
@@ -1,9 +1,20 @@
+use clap::Parser;
+use clap_verbosity_flag::{Verbosity, WarnLevel};
 use encoding_rs::WINDOWS_1251;
+use log::{debug, error};
 use num_bigint::{BigInt, ToBigInt};
 use num_integer::Integer;
 use num_traits::One;
 use std::process::ExitCode;
 
+/// Вскрытие RSA факторизацией модуля методом Ферма
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[command(flatten)]
+    verbosity: Verbosity<WarnLevel>,
+}
+
 fn fermat_factorization(n: &BigInt) -> (BigInt, BigInt) {
     let mut a = n.sqrt() + 1.to_bigint().unwrap();
     loop {
@@ -30,8 +41,10 @@ fn mod_inverse(e: &BigInt, phi: &BigInt) -> Option<BigInt> {
 
 fn decrypt(n: &BigInt, e: &BigInt, c: &[BigInt]) -> Option<String> {
     let (p, q) = fermat_factorization(n);
+    debug!("Factorization: {n} = {p} * {q}");
     let phi = (&p - 1.to_bigint().unwrap()) * (&q - 1.to_bigint().unwrap());
     let d = mod_inverse(e, &phi)?;
+    debug!("Private exponent: {d}");
     let mut message = String::new();
     for c_block in c {
         let m = c_block.modpow(&d, n);
@@ -45,6 +58,12 @@ fn decrypt(n: &BigInt, e: &BigInt, c: &[BigInt]) -> Option<String> {
 }
 
 fn main() -> ExitCode {
+    let args = Args::parse();
+    env_logger::Builder::new()
+        .filter_level(args.verbosity.log_level_filter())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
     let c = [
         BigInt::from(32279109612093u64),
         BigInt::from(17838629182964u64),
@@ -60,9 +79,9 @@ fn main() -> ExitCode {
         BigInt::from(19569174668782u64),
     ];
     if let Some(result) = decrypt(&BigInt::from(59046883376179u64), &BigInt::from(4044583), &c) {
-        println!("Result: '{result}'",);
+        println!("{result}");
     } else {
-        print!("[ERROR]");
+        error!("Failed to decrypt the message");
         return ExitCode::FAILURE;
     }
     ExitCode::SUCCESS
@@ -1,16 +1,30 @@
+use clap::Parser;
+use clap_verbosity_flag::{Verbosity, WarnLevel};
 use encoding_rs::WINDOWS_1251;
+use log::{debug, error};
 use num_bigint::BigInt;
 use std::process::ExitCode;
 
+/// Вскрытие RSA методом повторного шифрования
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[command(flatten)]
+    verbosity: Verbosity<WarnLevel>,
+}
+
 fn decrypt(n: &BigInt, e: &BigInt, c: &[BigInt]) -> Option<String> {
     let mut ans = String::new();
     for now in c {
         let mut yi = now.modpow(e, n);
         let mut result = BigInt::ZERO;
+        let mut steps = 1;
         while yi != *now {
             result = yi.clone();
             yi = yi.modpow(e, n);
+            steps += 1;
         }
+        debug!("Block {now}: cycle of {steps} encryptions");
         let (_, bytes) = result.to_bytes_be();
         match WINDOWS_1251.decode(&bytes) {
             (str, _, false) => ans.push_str(&str),
@@ -21,6 +35,12 @@ fn decrypt(n: &BigInt, e: &BigInt, c: &[BigInt]) -> Option<String> {
 }
 
 fn main() -> ExitCode {
+    let args = Args::parse();
+    env_logger::Builder::new()
+        .filter_level(args.verbosity.log_level_filter())
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
     let numbers = [
         BigInt::from(54879925681459u64),
         BigInt::from(72167008182929u64),
@@ -40,9 +60,9 @@ fn main() -> ExitCode {
         &BigInt::from(2581907),
         &numbers,
     ) {
-        println!("Result: '{result}'",);
+        println!("{result}");
     } else {
-        print!("[ERROR]");
+        error!("Failed to decrypt the message");
         return ExitCode::FAILURE;
     }
     ExitCode::SUCCESS